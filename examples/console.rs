@@ -0,0 +1,171 @@
+//! A minimal serial console client, similar to `virsh console`.
+//!
+//! Usage: console <domain> [device]
+//!        console <domain> --channel <name>
+//!
+//! Press Ctrl-] to disconnect.
+
+use std::{
+    env,
+    io::{self, Read, Write},
+    mem, process,
+    sync::mpsc,
+    thread,
+};
+use virt::{
+    connection::Connection,
+    domain::{ChannelFlags, ConsoleFlags},
+    stream::{StreamEvents, StreamFlags},
+};
+
+/// The byte sent by Ctrl-], used to disconnect.
+const ESCAPE: u8 = 0x1d;
+
+enum Message {
+    Input(Vec<u8>),
+    Event(StreamEvents),
+}
+
+/// Puts the terminal in raw mode, and restores the original mode when dropped.
+struct RawMode(libc::termios);
+
+impl RawMode {
+    fn enable() -> io::Result<Self> {
+        let mut original: libc::termios = unsafe { mem::zeroed() };
+        if unsafe { libc::tcgetattr(libc::STDIN_FILENO, &mut original) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        let mut raw = original;
+        unsafe { libc::cfmakeraw(&mut raw) };
+        if unsafe { libc::tcsetattr(libc::STDIN_FILENO, libc::TCSAFLUSH, &raw) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(RawMode(original))
+    }
+}
+
+impl Drop for RawMode {
+    fn drop(&mut self) {
+        unsafe { libc::tcsetattr(libc::STDIN_FILENO, libc::TCSAFLUSH, &self.0) };
+    }
+}
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let (domain_name, channel, device) = match args.as_slice() {
+        [domain] => (domain, false, None),
+        [domain, flag, name] if flag == "--channel" => (domain, true, Some(name.as_str())),
+        [domain, device] => (domain, false, Some(device.as_str())),
+        _ => {
+            eprintln!("Usage: console <domain> [device | --channel <name>]");
+            process::exit(1);
+        }
+    };
+
+    // The event loop must be registered before the connection is opened.
+    virt::event::register_default_impl().expect("Unable to register event loop");
+    thread::spawn(|| loop {
+        if let Err(e) = virt::event::run_default_impl() {
+            eprintln!("Event loop failed: {}", e);
+        }
+    });
+
+    let connection = Connection::builder().open().expect("Unable to connect");
+    let domain = connection
+        .domain_by_name(domain_name)
+        .expect("Unable to find domain");
+    let mut stream = if channel {
+        domain.open_channel(device, StreamFlags::NONBLOCK, ChannelFlags::empty())
+    } else {
+        domain.open_console(device, StreamFlags::NONBLOCK, ConsoleFlags::SAFE)
+    }
+    .expect("Unable to open console");
+
+    let (tx, rx) = mpsc::sync_channel(16);
+    let event_tx = tx.clone();
+    stream
+        .add_event_callback(
+            StreamEvents::READABLE | StreamEvents::ERROR | StreamEvents::HANGUP,
+            move |events| {
+                // Events are level triggered and will be delivered again if dropped here.
+                let _ = event_tx.try_send(Message::Event(events));
+            },
+        )
+        .expect("Unable to watch console stream");
+    thread::spawn(move || {
+        let mut buf = [0; 1024];
+        while let Ok(n) = io::stdin().read(&mut buf) {
+            if n == 0 || tx.send(Message::Input(buf[..n].to_vec())).is_err() {
+                break;
+            }
+        }
+    });
+
+    eprintln!("Connected to {}. Escape character is ^]", domain_name);
+    let raw_mode = RawMode::enable().expect("Unable to put terminal in raw mode");
+    let mut stdout = io::stdout();
+    let mut pending_input = Vec::new();
+    let mut buf = [0; 4096];
+    'outer: for message in rx {
+        match message {
+            Message::Input(input) => {
+                if let Some(pos) = input.iter().position(|&b| b == ESCAPE) {
+                    pending_input.extend_from_slice(&input[..pos]);
+                    flush_input(&mut stream, &mut pending_input);
+                    break;
+                }
+                pending_input.extend_from_slice(&input);
+            }
+            Message::Event(events) => {
+                if events.intersects(StreamEvents::ERROR | StreamEvents::HANGUP) {
+                    break;
+                }
+                loop {
+                    match stream.recv(&mut buf) {
+                        Ok(Some(0)) => break 'outer,
+                        Ok(Some(n)) => {
+                            stdout.write_all(&buf[..n]).unwrap();
+                            stdout.flush().unwrap();
+                        }
+                        Ok(None) => break,
+                        Err(e) => {
+                            eprintln!("Error reading from console: {}", e);
+                            break 'outer;
+                        }
+                    }
+                }
+            }
+        }
+        // Only ask to be woken up for writability while there is input waiting to be sent.
+        let mut events = StreamEvents::READABLE | StreamEvents::ERROR | StreamEvents::HANGUP;
+        if !flush_input(&mut stream, &mut pending_input) {
+            events |= StreamEvents::WRITABLE;
+        }
+        if let Err(e) = stream.update_event_callback(events) {
+            eprintln!("Unable to watch console stream: {}", e);
+            break;
+        }
+    }
+    drop(raw_mode);
+
+    if let Err(e) = stream.finish() {
+        eprintln!("\nError closing console: {}", e);
+    }
+}
+
+/// Sends as much of the pending input as possible. Returns true if all of it was sent.
+fn flush_input(stream: &mut virt::Stream, pending_input: &mut Vec<u8>) -> bool {
+    while !pending_input.is_empty() {
+        match stream.send(pending_input) {
+            Ok(Some(n)) => {
+                pending_input.drain(..n);
+            }
+            Ok(None) => return false,
+            Err(e) => {
+                eprintln!("Error writing to console: {}", e);
+                pending_input.clear();
+            }
+        }
+    }
+    true
+}
@@ -0,0 +1,245 @@
+use crate::{
+    util::{opt_ptr, take_string, take_wrapper_array, xml_cstring},
+    Domain, Error, VirtError, Wrapper,
+};
+use std::{
+    ffi::{CStr, CString},
+    mem, ptr,
+};
+
+bitflags::bitflags! {
+    /// Flags affecting how a checkpoint is created with [Domain::create_checkpoint].
+    pub struct CheckpointCreateFlags: u32 {
+        /// Restore or alter the metadata of an existing checkpoint, instead of creating a new
+        /// one.
+        const REDEFINE = virt_sys::VIR_DOMAIN_CHECKPOINT_CREATE_REDEFINE;
+        /// Use the guest agent to freeze the guest file systems while the checkpoint is created.
+        const QUIESCE = virt_sys::VIR_DOMAIN_CHECKPOINT_CREATE_QUIESCE;
+    }
+}
+
+bitflags::bitflags! {
+    /// Flags affecting [DomainCheckpoint::xml_desc].
+    pub struct CheckpointXmlFlags: u32 {
+        /// Include security sensitive information in the XML.
+        const SECURE = virt_sys::VIR_DOMAIN_CHECKPOINT_XML_SECURE;
+        /// Leave out the `<domain>` element describing the domain at checkpoint time.
+        const NO_DOMAIN = virt_sys::VIR_DOMAIN_CHECKPOINT_XML_NO_DOMAIN;
+        /// Include the amount of data changed on each disk since the checkpoint.
+        const SIZE = virt_sys::VIR_DOMAIN_CHECKPOINT_XML_SIZE;
+    }
+}
+
+bitflags::bitflags! {
+    /// Flags filtering the checkpoints returned by [Domain::list_all_checkpoints] and
+    /// [DomainCheckpoint::children].
+    pub struct CheckpointListFlags: u32 {
+        /// With [Domain::list_all_checkpoints], only list checkpoints without a parent.
+        const ROOTS = virt_sys::VIR_DOMAIN_CHECKPOINT_LIST_ROOTS;
+        /// With [DomainCheckpoint::children], list all descendants instead of only direct
+        /// children.
+        const DESCENDANTS = virt_sys::VIR_DOMAIN_CHECKPOINT_LIST_DESCENDANTS;
+        /// Sort the list so that parents come before their children.
+        const TOPOLOGICAL = virt_sys::VIR_DOMAIN_CHECKPOINT_LIST_TOPOLOGICAL;
+        /// Only list checkpoints without children.
+        const LEAVES = virt_sys::VIR_DOMAIN_CHECKPOINT_LIST_LEAVES;
+        /// Only list checkpoints with children.
+        const NO_LEAVES = virt_sys::VIR_DOMAIN_CHECKPOINT_LIST_NO_LEAVES;
+    }
+}
+
+bitflags::bitflags! {
+    /// Flags affecting [DomainCheckpoint::delete].
+    pub struct CheckpointDeleteFlags: u32 {
+        /// Also delete all children of the checkpoint.
+        const CHILDREN = virt_sys::VIR_DOMAIN_CHECKPOINT_DELETE_CHILDREN;
+        /// Only delete the libvirt metadata, leaving the bitmaps in the disk images in place.
+        const METADATA_ONLY = virt_sys::VIR_DOMAIN_CHECKPOINT_DELETE_METADATA_ONLY;
+        /// Only delete the children of the checkpoint, keeping the checkpoint itself.
+        const CHILDREN_ONLY = virt_sys::VIR_DOMAIN_CHECKPOINT_DELETE_CHILDREN_ONLY;
+    }
+}
+
+bitflags::bitflags! {
+    /// Flags affecting [Domain::backup_begin].
+    pub struct BackupBeginFlags: u32 {
+        /// Write a push mode backup into existing files, instead of creating them.
+        const REUSE_EXTERNAL = virt_sys::VIR_DOMAIN_BACKUP_BEGIN_REUSE_EXTERNAL;
+    }
+}
+
+/// A checkpoint of a [Domain], tracking which disk blocks changed since it was created. Used as
+/// the starting point of incremental backups.
+pub struct DomainCheckpoint(virt_sys::virDomainCheckpointPtr);
+
+// Safety: libvirt is thread safe since 0.6.0. It can handle multiple threads making calls to the
+// same virDomainCheckpoint instance.
+unsafe impl Send for DomainCheckpoint {}
+unsafe impl Sync for DomainCheckpoint {}
+
+impl DomainCheckpoint {
+    /// Returns the name of the checkpoint.
+    pub fn name(&self) -> Result<String, Error> {
+        let name_ptr = cvt_null!(unsafe { virt_sys::virDomainCheckpointGetName(self.0) })?;
+        unsafe { CStr::from_ptr(name_ptr) }
+            .to_str()
+            .map(str::to_owned)
+            .map_err(Error::Utf8Error)
+    }
+
+    /// Returns the parent of the checkpoint, or `None` if it is a root checkpoint.
+    pub fn parent(&self) -> Result<Option<DomainCheckpoint>, VirtError> {
+        match cvt_null!(unsafe { virt_sys::virDomainCheckpointGetParent(self.0, 0) }) {
+            Ok(ptr) => Ok(Some(DomainCheckpoint(ptr))),
+            Err(e) if e.is_code(virt_sys::VIR_ERR_NO_DOMAIN_CHECKPOINT) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Returns the XML description of the checkpoint.
+    pub fn xml_desc(&self, flags: CheckpointXmlFlags) -> Result<String, Error> {
+        let xml_ptr =
+            cvt_null!(unsafe { virt_sys::virDomainCheckpointGetXMLDesc(self.0, flags.bits()) })?;
+        unsafe { take_string(xml_ptr) }
+    }
+
+    /// Returns the children of this checkpoint, filtered by `flags`. Use
+    /// [CheckpointListFlags::DESCENDANTS] to get all descendants.
+    pub fn children(&self, flags: CheckpointListFlags) -> Result<Vec<DomainCheckpoint>, VirtError> {
+        let mut checkpoints = ptr::null_mut();
+        match unsafe {
+            virt_sys::virDomainCheckpointListAllChildren(self.0, &mut checkpoints, flags.bits())
+        } {
+            -1 => Err(VirtError::last_virt_error()),
+            n => Ok(unsafe { take_wrapper_array(checkpoints, n as usize) }),
+        }
+    }
+
+    /// Delete the checkpoint. Unless [CheckpointDeleteFlags::CHILDREN] is given, the changes
+    /// tracked by it are merged into its parent. The checkpoint object should not be used for
+    /// anything but freeing after this.
+    pub fn delete(&self, flags: CheckpointDeleteFlags) -> Result<(), VirtError> {
+        match unsafe { virt_sys::virDomainCheckpointDelete(self.0, flags.bits()) } {
+            -1 => Err(VirtError::last_virt_error()),
+            _ => Ok(()),
+        }
+    }
+
+    /// Free the checkpoint object. The checkpoint itself is kept.
+    /// If this is not explicitly called it will be called by the `Drop` implementation. And any
+    /// error will be logged to the error level.
+    ///
+    /// The only reason to call this explicitly is if you want to handle the error in some other
+    /// way than just logging it.
+    pub fn free(self) -> Result<(), VirtError> {
+        let result = self.free_internal();
+        mem::forget(self);
+        result
+    }
+
+    fn free_internal(&self) -> Result<(), VirtError> {
+        match unsafe { virt_sys::virDomainCheckpointFree(self.0) } {
+            -1 => Err(VirtError::last_virt_error()),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl Domain {
+    /// Create a checkpoint of the domain, as described by the checkpoint XML.
+    pub fn create_checkpoint(
+        &self,
+        xml: &str,
+        flags: CheckpointCreateFlags,
+    ) -> Result<DomainCheckpoint, Error> {
+        let xml_cstr = CString::new(xml).map_err(Error::InvalidXml)?;
+        let ptr = cvt_null!(unsafe {
+            virt_sys::virDomainCheckpointCreateXML(self.as_ptr(), xml_cstr.as_ptr(), flags.bits())
+        })?;
+        Ok(DomainCheckpoint(ptr))
+    }
+
+    /// Returns all checkpoints of the domain, filtered by `flags`.
+    pub fn list_all_checkpoints(
+        &self,
+        flags: CheckpointListFlags,
+    ) -> Result<Vec<DomainCheckpoint>, VirtError> {
+        let mut checkpoints = ptr::null_mut();
+        match unsafe {
+            virt_sys::virDomainListAllCheckpoints(self.as_ptr(), &mut checkpoints, flags.bits())
+        } {
+            -1 => Err(VirtError::last_virt_error()),
+            n => Ok(unsafe { take_wrapper_array(checkpoints, n as usize) }),
+        }
+    }
+
+    /// Look up a checkpoint of the domain by its name.
+    pub fn checkpoint_by_name(&self, name: &str) -> Result<DomainCheckpoint, Error> {
+        let name_cstr = CString::new(name).map_err(Error::InvalidName)?;
+        let ptr = cvt_null!(unsafe {
+            virt_sys::virDomainCheckpointLookupByName(self.as_ptr(), name_cstr.as_ptr(), 0)
+        })?;
+        Ok(DomainCheckpoint(ptr))
+    }
+
+    /// Start a backup job of the domain's disks, as described by the backup XML. If
+    /// `checkpoint_xml` is given, a checkpoint is created atomically with the start of the
+    /// backup, so that the next backup can be incremental from this one.
+    ///
+    /// The backup runs as a job of the domain, which can be monitored with
+    /// [Domain::job_stats] and cancelled with [Domain::abort_job].
+    pub fn backup_begin(
+        &self,
+        backup_xml: &str,
+        checkpoint_xml: Option<&str>,
+        flags: BackupBeginFlags,
+    ) -> Result<(), Error> {
+        let backup_cstr = CString::new(backup_xml).map_err(Error::InvalidXml)?;
+        let checkpoint_cstr = xml_cstring(checkpoint_xml)?;
+        match unsafe {
+            virt_sys::virDomainBackupBegin(
+                self.as_ptr(),
+                backup_cstr.as_ptr(),
+                opt_ptr(&checkpoint_cstr),
+                flags.bits(),
+            )
+        } {
+            -1 => Err(Error::from(VirtError::last_virt_error())),
+            _ => Ok(()),
+        }
+    }
+
+    /// Returns the XML description of the currently running backup job.
+    pub fn backup_xml_desc(&self) -> Result<String, Error> {
+        let xml_ptr = cvt_null!(unsafe { virt_sys::virDomainBackupGetXMLDesc(self.as_ptr(), 0) })?;
+        unsafe { take_string(xml_ptr) }
+    }
+}
+
+impl crate::Wrapper for DomainCheckpoint {
+    type Ptr = virt_sys::virDomainCheckpointPtr;
+
+    unsafe fn from_ptr(ptr: Self::Ptr) -> Self {
+        Self(ptr)
+    }
+
+    fn as_ptr(&self) -> Self::Ptr {
+        self.0
+    }
+}
+
+impl Clone for DomainCheckpoint {
+    fn clone(&self) -> Self {
+        let ret = unsafe { virt_sys::virDomainCheckpointRef(self.0) };
+        assert_eq!(ret, 0, "Unexpected error from virDomainCheckpointRef");
+        Self(self.0)
+    }
+}
+
+impl Drop for DomainCheckpoint {
+    fn drop(&mut self) {
+        if let Err(e) = self.free_internal() {
+            log::error!("Error when freeing domain checkpoint: {}", e);
+        }
+    }
+}
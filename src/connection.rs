@@ -1,8 +1,14 @@
-use crate::{Domain, Error, VirtError};
+use crate::{
+    domain::{CreateFlags, SaveRestoreFlags, XmlFlags},
+    network::ListAllNetworksFlags,
+    util::{opt_ptr, path_cstring, take_string, xml_cstring},
+    Domain, Error, Network, StoragePool, VirtError,
+};
 use std::{
     ffi::{CStr, CString},
     mem,
-    os::raw::c_ulong,
+    os::{raw::c_ulong, unix::io::BorrowedFd},
+    path::Path,
     ptr,
 };
 
@@ -172,6 +178,121 @@ impl Connection {
         Domain::create_from_xml(self, xml, flags)
     }
 
+    /// Create and start a transient domain, like [Connection::create_domain], passing the file
+    /// descriptors in `files` to the init process of the guest. Only supported by container
+    /// based hypervisors, such as LXC, where the guest inherits them starting from file
+    /// descriptor 3.
+    ///
+    /// The file descriptors remain owned by the caller, and can be closed after this returns.
+    pub fn create_domain_with_files(
+        &self,
+        xml: &str,
+        files: &[BorrowedFd<'_>],
+        flags: CreateFlags,
+    ) -> Result<Domain, Error> {
+        Domain::create_from_xml_with_files(self, xml, files, flags)
+    }
+
+    /// Look up a domain on this connection by its name.
+    pub fn domain_by_name(&self, name: &str) -> Result<Domain, Error> {
+        Domain::lookup_by_name(self, name)
+    }
+
+    /// Restore a domain previously saved to the file at `path` with [Domain::save].
+    ///
+    /// `xml` may supply an alternative domain XML to use instead of the one stored in the state
+    /// file. It must only change host-specific portions of the domain, such as disk paths.
+    pub fn restore_domain(
+        &self,
+        path: &Path,
+        xml: Option<&str>,
+        flags: SaveRestoreFlags,
+    ) -> Result<(), Error> {
+        let path_cstr = path_cstring(path)?;
+        let xml_cstr = xml_cstring(xml)?;
+        match unsafe {
+            virt_sys::virDomainRestoreFlags(
+                self.0,
+                path_cstr.as_ptr(),
+                opt_ptr(&xml_cstr),
+                flags.bits(),
+            )
+        } {
+            -1 => Err(Error::from(VirtError::last_virt_error())),
+            _ => Ok(()),
+        }
+    }
+
+    /// Returns the domain XML stored in the state file at `path`, created by [Domain::save].
+    /// Only [XmlFlags::SECURE] is supported, it includes security sensitive information in the
+    /// XML.
+    pub fn save_image_xml_desc(&self, path: &Path, flags: XmlFlags) -> Result<String, Error> {
+        let path_cstr = path_cstring(path)?;
+        let xml_ptr = cvt_null!(unsafe {
+            virt_sys::virDomainSaveImageGetXMLDesc(self.0, path_cstr.as_ptr(), flags.bits())
+        })?;
+        unsafe { take_string(xml_ptr) }
+    }
+
+    /// Replace the domain XML stored in the state file at `path`. The new XML must only change
+    /// host-specific portions of the domain, such as disk paths. [SaveRestoreFlags::RUNNING] or
+    /// [SaveRestoreFlags::PAUSED] can be used to alter the state the domain will be restored
+    /// into.
+    pub fn save_image_define_xml(
+        &self,
+        path: &Path,
+        xml: &str,
+        flags: SaveRestoreFlags,
+    ) -> Result<(), Error> {
+        let path_cstr = path_cstring(path)?;
+        let xml_cstr = CString::new(xml).map_err(Error::InvalidXml)?;
+        match unsafe {
+            virt_sys::virDomainSaveImageDefineXML(
+                self.0,
+                path_cstr.as_ptr(),
+                xml_cstr.as_ptr(),
+                flags.bits(),
+            )
+        } {
+            -1 => Err(Error::from(VirtError::last_virt_error())),
+            _ => Ok(()),
+        }
+    }
+
+    /// Create and start a transient network from its XML description.
+    pub fn create_network(&self, xml: &str) -> Result<Network, Error> {
+        Network::create_from_xml(self, xml)
+    }
+
+    /// Define a persistent network from its XML description, or update the configuration of
+    /// an existing one. The network is not started.
+    pub fn define_network(&self, xml: &str) -> Result<Network, Error> {
+        Network::define_from_xml(self, xml)
+    }
+
+    /// Look up a network on this connection by its name.
+    pub fn network_by_name(&self, name: &str) -> Result<Network, Error> {
+        Network::lookup_by_name(self, name)
+    }
+
+    /// Look up a network on this connection by its UUID, in binary form.
+    pub fn network_by_uuid(&self, uuid: &[u8; 16]) -> Result<Network, VirtError> {
+        Network::lookup_by_uuid(self, uuid)
+    }
+
+    /// Returns the networks on this connection, filtered by `flags`.
+    pub fn list_all_networks(
+        &self,
+        flags: ListAllNetworksFlags,
+    ) -> Result<Vec<Network>, VirtError> {
+        Network::list_all(self, flags)
+    }
+
+    /// Look up a storage pool on this connection by its name.
+    pub fn storage_pool_by_name(&self, name: &str) -> Result<StoragePool, Error> {
+        StoragePool::lookup_by_name(self, name)
+    }
+
     /// Closes the connection. If this connection has been cloned it just decrements the
     /// reference count. The connection is actually closed when the last instance is closed.
     /// This happens automatically in the `Drop` implementation if not explicitly called.
@@ -1,5 +1,52 @@
-use crate::{wrapper::Wrapper, Connection, Error, VirtError};
-use std::{ffi::CString, mem};
+use crate::{
+    stream::StreamFlags,
+    util::{opt_ptr, path_cstring, take_string, xml_cstring},
+    wrapper::Wrapper,
+    Connection, Error, Stream, VirtError,
+};
+use std::{
+    ffi::CString,
+    mem,
+    os::raw::{c_int, c_uint},
+    os::unix::{
+        io::{AsRawFd, BorrowedFd, FromRawFd},
+        net::UnixStream,
+    },
+    path::Path,
+};
+
+mod agent;
+#[cfg(feature = "libvirt-5-10-0")]
+pub use agent::AgentResponseTimeout;
+#[cfg(feature = "libvirt-5-7-0")]
+pub use agent::{
+    GuestDisk, GuestFilesystem, GuestInfo, GuestInfoTypes, GuestOsInfo, GuestTimezone, GuestUser,
+};
+pub use agent::{GuestVcpus, SetTimeFlags, SetUserPasswordFlags};
+
+mod bandwidth;
+pub use bandwidth::{Bandwidth, InterfaceBandwidth};
+
+mod fs;
+pub use fs::{FreezeGuard, FsInfo};
+
+mod input;
+pub use input::{KeycodeSet, ProcessSignal};
+
+mod interface;
+pub use interface::{GuestInterface, InterfaceAddressesSource, IpNet};
+
+mod job;
+pub use job::{JobOperation, JobStats, JobStatsFlags, JobType};
+
+mod metadata;
+pub use metadata::MetadataKind;
+
+mod migration;
+pub use migration::{MigrateCompression, MigrateFlags, MigrateMaxSpeedFlags, MigrateParams};
+
+mod perf;
+pub use perf::PerfEvents;
 
 bitflags::bitflags! {
     /// Flags affecting the starting of transient domains
@@ -29,6 +76,115 @@ bitflags::bitflags! {
     }
 }
 
+bitflags::bitflags! {
+    /// Selects whether a change applies to the running domain, its persistent configuration,
+    /// or both.
+    pub struct ModificationImpact: u32 {
+        /// Affect the running domain if it is active, and its configuration otherwise.
+        const CURRENT = virt_sys::VIR_DOMAIN_AFFECT_CURRENT;
+        /// Affect the running domain.
+        const LIVE = virt_sys::VIR_DOMAIN_AFFECT_LIVE;
+        /// Affect the persistent configuration of the domain.
+        const CONFIG = virt_sys::VIR_DOMAIN_AFFECT_CONFIG;
+    }
+}
+
+bitflags::bitflags! {
+    /// Flags affecting saving a domain to, and restoring a domain from, a state file.
+    pub struct SaveRestoreFlags: u32 {
+        /// Avoid file system cache pollution when reading or writing the state file.
+        const BYPASS_CACHE = virt_sys::VIR_DOMAIN_SAVE_BYPASS_CACHE;
+        /// Override the state recorded in the image and start the domain running.
+        const RUNNING = virt_sys::VIR_DOMAIN_SAVE_RUNNING;
+        /// Override the state recorded in the image and start the domain paused.
+        const PAUSED = virt_sys::VIR_DOMAIN_SAVE_PAUSED;
+    }
+}
+
+bitflags::bitflags! {
+    /// Flags affecting what is included in a domain XML description.
+    pub struct XmlFlags: u32 {
+        /// Include security sensitive information, such as passwords.
+        const SECURE = virt_sys::VIR_DOMAIN_XML_SECURE;
+        /// Describe the inactive (persistent) configuration rather than the running one.
+        const INACTIVE = virt_sys::VIR_DOMAIN_XML_INACTIVE;
+        /// Update the guest CPU requirements according to the host CPU.
+        const UPDATE_CPU = virt_sys::VIR_DOMAIN_XML_UPDATE_CPU;
+        /// Produce XML suitable for migration to an older libvirt.
+        const MIGRATABLE = virt_sys::VIR_DOMAIN_XML_MIGRATABLE;
+    }
+}
+
+bitflags::bitflags! {
+    /// Flags affecting how a core dump is taken with [Domain::core_dump].
+    pub struct CoreDumpFlags: u32 {
+        /// Crash the domain after the dump is complete.
+        const CRASH = virt_sys::VIR_DUMP_CRASH;
+        /// Dump without pausing the domain. The dump may be inconsistent.
+        const LIVE = virt_sys::VIR_DUMP_LIVE;
+        /// Avoid file system cache pollution.
+        const BYPASS_CACHE = virt_sys::VIR_DUMP_BYPASS_CACHE;
+        /// Reset the domain after the dump is complete.
+        const RESET = virt_sys::VIR_DUMP_RESET;
+        /// Only dump the guest memory, in a format readable by tools such as `crash`.
+        const MEMORY_ONLY = virt_sys::VIR_DUMP_MEMORY_ONLY;
+    }
+}
+
+/// The file format of a core dump. Anything other than [CoreDumpFormat::Raw] requires
+/// [CoreDumpFlags::MEMORY_ONLY].
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum CoreDumpFormat {
+    /// Dump the guest memory as-is.
+    Raw,
+    /// kdump-compressed format, with zlib compression.
+    KdumpZlib,
+    /// kdump-compressed format, with lzo compression.
+    KdumpLzo,
+    /// kdump-compressed format, with snappy compression.
+    KdumpSnappy,
+}
+
+impl CoreDumpFormat {
+    fn to_raw(self) -> virt_sys::virDomainCoreDumpFormat {
+        match self {
+            CoreDumpFormat::Raw => virt_sys::VIR_DOMAIN_CORE_DUMP_FORMAT_RAW,
+            CoreDumpFormat::KdumpZlib => virt_sys::VIR_DOMAIN_CORE_DUMP_FORMAT_KDUMP_ZLIB,
+            CoreDumpFormat::KdumpLzo => virt_sys::VIR_DOMAIN_CORE_DUMP_FORMAT_KDUMP_LZO,
+            CoreDumpFormat::KdumpSnappy => virt_sys::VIR_DOMAIN_CORE_DUMP_FORMAT_KDUMP_SNAPPY,
+        }
+    }
+}
+
+bitflags::bitflags! {
+    /// Flags affecting how a console is opened with [Domain::open_console].
+    pub struct ConsoleFlags: u32 {
+        /// Disconnect any other client already connected to the console, instead of failing.
+        const FORCE = virt_sys::VIR_DOMAIN_CONSOLE_FORCE;
+        /// Only open the console if the hypervisor driver supports safely preventing concurrent
+        /// access to it.
+        const SAFE = virt_sys::VIR_DOMAIN_CONSOLE_SAFE;
+    }
+}
+
+bitflags::bitflags! {
+    /// Flags affecting how a channel is opened with [Domain::open_channel].
+    pub struct ChannelFlags: u32 {
+        /// Disconnect any other client already connected to the channel, instead of failing.
+        const FORCE = virt_sys::VIR_DOMAIN_CHANNEL_FORCE;
+    }
+}
+
+bitflags::bitflags! {
+    /// Flags affecting how a connection to a graphics server is set up with
+    /// [Domain::open_graphics] and [Domain::open_graphics_fd].
+    pub struct GraphicsFlags: u32 {
+        /// Skip authentication of the client. The client is granted access without having to
+        /// present the password of the graphics server.
+        const SKIPAUTH = virt_sys::VIR_DOMAIN_OPEN_GRAPHICS_SKIPAUTH;
+    }
+}
+
 /// Various ways to handle the termination of a domain when calling [Domain::destroy].
 pub enum DestroyMode {
     /// Never forcefully kill the domain. If it does not shut down gracefully in a timely manner,
@@ -43,6 +199,36 @@ pub enum DestroyMode {
     KillAfterTimeout,
 }
 
+/// The lifecycle events of a domain that [Domain::set_lifecycle_action] configures the
+/// reaction to.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum LifecycleType {
+    /// The guest powered itself off.
+    Poweroff,
+    /// The guest rebooted itself.
+    Reboot,
+    /// The guest crashed.
+    Crash,
+}
+
+/// How to react to a [LifecycleType] event.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum LifecycleAction {
+    /// Terminate the domain.
+    Destroy,
+    /// Terminate the domain and start it again with the same configuration.
+    Restart,
+    /// Rename the terminated domain, and start a new domain with the original name and
+    /// configuration.
+    RestartRename,
+    /// Keep the domain around in its current state, for analysis.
+    Preserve,
+    /// Dump the memory of the domain to disk, then terminate it.
+    CoredumpDestroy,
+    /// Dump the memory of the domain to disk, then restart it.
+    CoredumpRestart,
+}
+
 pub struct Domain(virt_sys::virDomainPtr);
 
 // Safety: libvirt is thread safe since 0.6.0. It can handle multiple threads making calls to the
@@ -64,6 +250,60 @@ impl Domain {
         Ok(Domain(ptr))
     }
 
+    /// See [Connection::create_domain_with_files].
+    pub(crate) fn create_from_xml_with_files(
+        connection: &Connection,
+        xml: &str,
+        files: &[BorrowedFd<'_>],
+        flags: CreateFlags,
+    ) -> Result<Self, Error> {
+        let xml_cstr = CString::new(xml).map_err(Error::InvalidXml)?;
+        let mut fds = raw_fds(files);
+        let ptr = cvt_null!(unsafe {
+            virt_sys::virDomainCreateXMLWithFiles(
+                connection.as_ptr(),
+                xml_cstr.as_ptr(),
+                fds.len() as c_uint,
+                fds.as_mut_ptr(),
+                flags.bits(),
+            )
+        })?;
+        Ok(Domain(ptr))
+    }
+
+    /// Start this defined, but inactive, domain. The file descriptors in `files` are passed to
+    /// the init process of the guest. Only supported by container based hypervisors, such as
+    /// LXC, where the guest inherits them starting from file descriptor 3.
+    ///
+    /// The file descriptors remain owned by the caller, and can be closed after this returns.
+    pub fn create_with_files(
+        &self,
+        files: &[BorrowedFd<'_>],
+        flags: CreateFlags,
+    ) -> Result<(), VirtError> {
+        let mut fds = raw_fds(files);
+        match unsafe {
+            virt_sys::virDomainCreateWithFiles(
+                self.0,
+                fds.len() as c_uint,
+                fds.as_mut_ptr(),
+                flags.bits(),
+            )
+        } {
+            -1 => Err(VirtError::last_virt_error()),
+            _ => Ok(()),
+        }
+    }
+
+    /// See [Connection::domain_by_name].
+    pub(crate) fn lookup_by_name(connection: &Connection, name: &str) -> Result<Self, Error> {
+        let name_cstr = CString::new(name).map_err(Error::InvalidName)?;
+        let ptr = cvt_null!(unsafe {
+            virt_sys::virDomainLookupByName(connection.as_ptr(), name_cstr.as_ptr())
+        })?;
+        Ok(Domain(ptr))
+    }
+
     /// Attach a virtual device to a domain, using the flags parameter to control how the device is
     /// attached. [DeviceModifyFlags::CURRENT] specifies that the device allocation is made based
     /// on current domain state. [DeviceModifyFlags::LIVE] specifies that the device shall be
@@ -154,6 +394,284 @@ impl Domain {
         }
     }
 
+    /// Returns whether the domain is started automatically when the host boots.
+    pub fn autostart(&self) -> Result<bool, VirtError> {
+        let mut autostart = 0;
+        match unsafe { virt_sys::virDomainGetAutostart(self.0, &mut autostart) } {
+            -1 => Err(VirtError::last_virt_error()),
+            _ => Ok(autostart != 0),
+        }
+    }
+
+    /// Set whether the domain is started automatically when the host boots. Only persistent
+    /// domains can be started automatically.
+    pub fn set_autostart(&self, autostart: bool) -> Result<(), VirtError> {
+        match unsafe { virt_sys::virDomainSetAutostart(self.0, autostart as c_int) } {
+            -1 => Err(VirtError::last_virt_error()),
+            _ => Ok(()),
+        }
+    }
+
+    /// Configure how the domain reacts to the lifecycle event `lifecycle_type`.
+    pub fn set_lifecycle_action(
+        &self,
+        lifecycle_type: LifecycleType,
+        action: LifecycleAction,
+        flags: ModificationImpact,
+    ) -> Result<(), VirtError> {
+        let lifecycle_type = match lifecycle_type {
+            LifecycleType::Poweroff => virt_sys::VIR_DOMAIN_LIFECYCLE_POWEROFF,
+            LifecycleType::Reboot => virt_sys::VIR_DOMAIN_LIFECYCLE_REBOOT,
+            LifecycleType::Crash => virt_sys::VIR_DOMAIN_LIFECYCLE_CRASH,
+        };
+        let action = match action {
+            LifecycleAction::Destroy => virt_sys::VIR_DOMAIN_LIFECYCLE_ACTION_DESTROY,
+            LifecycleAction::Restart => virt_sys::VIR_DOMAIN_LIFECYCLE_ACTION_RESTART,
+            LifecycleAction::RestartRename => virt_sys::VIR_DOMAIN_LIFECYCLE_ACTION_RESTART_RENAME,
+            LifecycleAction::Preserve => virt_sys::VIR_DOMAIN_LIFECYCLE_ACTION_PRESERVE,
+            LifecycleAction::CoredumpDestroy => {
+                virt_sys::VIR_DOMAIN_LIFECYCLE_ACTION_COREDUMP_DESTROY
+            }
+            LifecycleAction::CoredumpRestart => {
+                virt_sys::VIR_DOMAIN_LIFECYCLE_ACTION_COREDUMP_RESTART
+            }
+        };
+        match unsafe {
+            virt_sys::virDomainSetLifecycleAction(self.0, lifecycle_type, action, flags.bits())
+        } {
+            -1 => Err(VirtError::last_virt_error()),
+            _ => Ok(()),
+        }
+    }
+
+    /// Suspend the domain and save its memory contents to the file at `path`. After this call
+    /// returns successfully the domain is no longer running. Use [Connection::restore_domain] to
+    /// start it again from the saved state.
+    ///
+    /// `xml` may supply an alternative domain XML to be used when the domain is later restored.
+    /// It must only change host-specific portions of the domain, such as disk paths. Use
+    /// [SaveRestoreFlags::RUNNING] or [SaveRestoreFlags::PAUSED] to override the state the
+    /// domain will be in after being restored.
+    pub fn save(
+        &self,
+        path: &Path,
+        xml: Option<&str>,
+        flags: SaveRestoreFlags,
+    ) -> Result<(), Error> {
+        let path_cstr = path_cstring(path)?;
+        let xml_cstr = xml_cstring(xml)?;
+        match unsafe {
+            virt_sys::virDomainSaveFlags(
+                self.0,
+                path_cstr.as_ptr(),
+                opt_ptr(&xml_cstr),
+                flags.bits(),
+            )
+        } {
+            -1 => Err(Error::from(VirtError::last_virt_error())),
+            _ => Ok(()),
+        }
+    }
+
+    /// Suspend the domain and save its memory contents to a file managed by libvirt. The next
+    /// time the domain is started it is automatically restored from this file, and the file is
+    /// removed.
+    ///
+    /// Only [SaveRestoreFlags::BYPASS_CACHE], [SaveRestoreFlags::RUNNING] and
+    /// [SaveRestoreFlags::PAUSED] are meaningful here, the last two control the state the domain
+    /// will be in once restored.
+    pub fn managed_save(&self, flags: SaveRestoreFlags) -> Result<(), VirtError> {
+        match unsafe { virt_sys::virDomainManagedSave(self.0, flags.bits()) } {
+            -1 => Err(VirtError::last_virt_error()),
+            _ => Ok(()),
+        }
+    }
+
+    /// Returns whether or not this domain has a managed save image, created by
+    /// [Domain::managed_save].
+    pub fn has_managed_save_image(&self) -> Result<bool, VirtError> {
+        match unsafe { virt_sys::virDomainHasManagedSaveImage(self.0, 0) } {
+            -1 => Err(VirtError::last_virt_error()),
+            0 => Ok(false),
+            _ => Ok(true),
+        }
+    }
+
+    /// Remove the managed save image of this domain, if any. The domain will boot from scratch
+    /// the next time it is started.
+    pub fn managed_save_remove(&self) -> Result<(), VirtError> {
+        match unsafe { virt_sys::virDomainManagedSaveRemove(self.0, 0) } {
+            -1 => Err(VirtError::last_virt_error()),
+            _ => Ok(()),
+        }
+    }
+
+    /// Returns the domain XML stored in the managed save image of this domain. Only
+    /// [XmlFlags::SECURE] is supported, it includes security sensitive information in the XML.
+    pub fn managed_save_xml_desc(&self, flags: XmlFlags) -> Result<String, Error> {
+        let xml_ptr =
+            cvt_null!(unsafe { virt_sys::virDomainManagedSaveGetXMLDesc(self.0, flags.bits()) })?;
+        unsafe { take_string(xml_ptr) }
+    }
+
+    /// Replace the domain XML stored in the managed save image of this domain. The new XML must
+    /// only change host-specific portions of the domain, such as disk paths.
+    /// [SaveRestoreFlags::RUNNING] or [SaveRestoreFlags::PAUSED] can be used to alter the state
+    /// the domain will be restored into.
+    pub fn managed_save_define_xml(&self, xml: &str, flags: SaveRestoreFlags) -> Result<(), Error> {
+        let xml_cstr = CString::new(xml).map_err(Error::InvalidXml)?;
+        match unsafe {
+            virt_sys::virDomainManagedSaveDefineXML(self.0, xml_cstr.as_ptr(), flags.bits())
+        } {
+            -1 => Err(Error::from(VirtError::last_virt_error())),
+            _ => Ok(()),
+        }
+    }
+
+    /// Dump the core of the domain to the file at `path`, in the given `format`, for analysis.
+    /// Unless [CoreDumpFlags::LIVE] is given the domain is paused while the dump is taken, and
+    /// resumed afterwards.
+    pub fn core_dump(
+        &self,
+        path: &Path,
+        format: CoreDumpFormat,
+        flags: CoreDumpFlags,
+    ) -> Result<(), Error> {
+        let path_cstr = path_cstring(path)?;
+        match unsafe {
+            virt_sys::virDomainCoreDumpWithFormat(
+                self.0,
+                path_cstr.as_ptr(),
+                format.to_raw(),
+                flags.bits(),
+            )
+        } {
+            -1 => Err(Error::from(VirtError::last_virt_error())),
+            _ => Ok(()),
+        }
+    }
+
+    /// Take a screenshot of the given `screen` of the domain. The first screen has index zero.
+    /// Returns the MIME type of the image, along with a [Stream] the image data can be read
+    /// from. The image format is chosen by the hypervisor.
+    pub fn screenshot(&self, screen: u32) -> Result<(String, Stream), Error> {
+        let mut stream = Stream::new_raw(self.connection_ptr()?, 0)?;
+        let mime_type_ptr = cvt_null!(unsafe {
+            virt_sys::virDomainScreenshot(self.0, stream.as_ptr(), screen, 0)
+        })?;
+        stream.set_active();
+        let mime_type = unsafe { take_string(mime_type_ptr) }?;
+        Ok((mime_type, stream))
+    }
+
+    /// Open a stream connected to a console of the domain. `dev_name` is the alias of the
+    /// console, serial or parallel device to connect to. If it is `None` the first console
+    /// or serial device is used.
+    ///
+    /// Use [StreamFlags::NONBLOCK] to get a stream suitable for event driven I/O. Since data can
+    /// be sent and received at any time, interactive consoles are best driven by an event loop.
+    pub fn open_console(
+        &self,
+        dev_name: Option<&str>,
+        stream_flags: StreamFlags,
+        flags: ConsoleFlags,
+    ) -> Result<Stream, Error> {
+        let dev_name_cstr = dev_name
+            .map(CString::new)
+            .transpose()
+            .map_err(Error::InvalidName)?;
+        let mut stream = Stream::new_raw(self.connection_ptr()?, stream_flags.bits())?;
+        match unsafe {
+            virt_sys::virDomainOpenConsole(
+                self.0,
+                opt_ptr(&dev_name_cstr),
+                stream.as_ptr(),
+                flags.bits(),
+            )
+        } {
+            -1 => Err(Error::from(VirtError::last_virt_error())),
+            _ => {
+                stream.set_active();
+                Ok(stream)
+            }
+        }
+    }
+
+    /// Open a stream connected to a data channel of the domain, such as a virtio-serial
+    /// channel. `name` is the name of the channel, as given by its `<target name='...'/>`. If
+    /// it is `None` the first channel is used.
+    ///
+    /// See [Domain::open_console] for the meaning of `stream_flags`.
+    pub fn open_channel(
+        &self,
+        name: Option<&str>,
+        stream_flags: StreamFlags,
+        flags: ChannelFlags,
+    ) -> Result<Stream, Error> {
+        let name_cstr = name
+            .map(CString::new)
+            .transpose()
+            .map_err(Error::InvalidName)?;
+        let mut stream = Stream::new_raw(self.connection_ptr()?, stream_flags.bits())?;
+        match unsafe {
+            virt_sys::virDomainOpenChannel(
+                self.0,
+                opt_ptr(&name_cstr),
+                stream.as_ptr(),
+                flags.bits(),
+            )
+        } {
+            -1 => Err(Error::from(VirtError::last_virt_error())),
+            _ => {
+                stream.set_active();
+                Ok(stream)
+            }
+        }
+    }
+
+    /// Open a connection to the VNC or SPICE server of the domain. `index` is the index of the
+    /// graphics device in the domain XML. Returns a UNIX socket connected to the server, ready
+    /// for the client to speak the protocol of the server over.
+    ///
+    /// This only works for connections to the local host, as the file descriptor can't be
+    /// passed over the network.
+    pub fn open_graphics_fd(
+        &self,
+        index: u32,
+        flags: GraphicsFlags,
+    ) -> Result<UnixStream, VirtError> {
+        match unsafe { virt_sys::virDomainOpenGraphicsFD(self.0, index, flags.bits()) } {
+            -1 => Err(VirtError::last_virt_error()),
+            fd => Ok(unsafe { UnixStream::from_raw_fd(fd) }),
+        }
+    }
+
+    /// Hand the given UNIX socket to the VNC or SPICE server of the domain, which will treat it
+    /// as a newly connected client. `index` is the index of the graphics device in the domain
+    /// XML. The caller keeps ownership of `socket`.
+    ///
+    /// This only works for connections to the local host, as the file descriptor can't be
+    /// passed over the network.
+    pub fn open_graphics(
+        &self,
+        index: u32,
+        socket: &impl AsRawFd,
+        flags: GraphicsFlags,
+    ) -> Result<(), VirtError> {
+        match unsafe {
+            virt_sys::virDomainOpenGraphics(self.0, index, socket.as_raw_fd(), flags.bits())
+        } {
+            -1 => Err(VirtError::last_virt_error()),
+            _ => Ok(()),
+        }
+    }
+
+    /// Returns the pointer to the connection this domain belongs to. The connection is not
+    /// referenced, so the pointer is only valid while this domain is alive.
+    fn connection_ptr(&self) -> Result<virt_sys::virConnectPtr, VirtError> {
+        cvt_null!(unsafe { virt_sys::virDomainGetConnect(self.0) })
+    }
+
     /// Free the domain object. The running instance is kept alive.
     /// If this is not explicitly called it will be called by the `Drop` implementation. And any
     /// error will be logged to the error level.
@@ -174,6 +692,10 @@ impl Domain {
     }
 }
 
+fn raw_fds(files: &[BorrowedFd<'_>]) -> Vec<c_int> {
+    files.iter().map(AsRawFd::as_raw_fd).collect()
+}
+
 impl crate::Wrapper for Domain {
     type Ptr = virt_sys::virDomainPtr;
 
@@ -0,0 +1,441 @@
+use super::{Domain, ModificationImpact};
+use crate::{typed_params::TypedParams, Error, VirtError};
+use std::{
+    ffi::CString,
+    mem,
+    os::raw::{c_char, c_int, c_uint},
+    ptr,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+#[cfg(feature = "libvirt-5-7-0")]
+bitflags::bitflags! {
+    /// The kinds of information to request with [Domain::guest_info]. No flags means all
+    /// information supported by the guest agent.
+    ///
+    /// Disk information (`VIR_DOMAIN_GUEST_INFO_DISKS`) needs libvirt 7.0.0, which is newer
+    /// than any version this crate has bindings for, so it can't be requested yet.
+    pub struct GuestInfoTypes: u32 {
+        /// The users logged into the guest.
+        const USERS = virt_sys::VIR_DOMAIN_GUEST_INFO_USERS;
+        /// The operating system running in the guest.
+        const OS = virt_sys::VIR_DOMAIN_GUEST_INFO_OS;
+        /// The timezone of the guest.
+        const TIMEZONE = virt_sys::VIR_DOMAIN_GUEST_INFO_TIMEZONE;
+        /// The hostname of the guest.
+        const HOSTNAME = virt_sys::VIR_DOMAIN_GUEST_INFO_HOSTNAME;
+        /// The file systems mounted in the guest, and the disks backing them.
+        const FILESYSTEM = virt_sys::VIR_DOMAIN_GUEST_INFO_FILESYSTEM;
+    }
+}
+
+bitflags::bitflags! {
+    /// Flags affecting [Domain::set_user_password].
+    pub struct SetUserPasswordFlags: u32 {
+        /// The password is already encrypted in the format the guest expects, such as a
+        /// `crypt(3)` hash on Linux.
+        const ENCRYPTED = virt_sys::VIR_DOMAIN_PASSWORD_ENCRYPTED;
+    }
+}
+
+bitflags::bitflags! {
+    /// Flags affecting [Domain::set_time].
+    pub struct SetTimeFlags: u32 {
+        /// Make the guest re-read its clock from the hardware clock, instead of setting it to
+        /// the given time.
+        const SYNC = virt_sys::VIR_DOMAIN_TIME_SYNC;
+    }
+}
+
+/// Information about a guest, as reported by the guest agent. Returned by
+/// [Domain::guest_info].
+///
+/// Information that was not requested, or that the guest agent could not provide, is left empty.
+#[cfg(feature = "libvirt-5-7-0")]
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct GuestInfo {
+    /// The users logged into the guest.
+    pub users: Vec<GuestUser>,
+    /// The operating system running in the guest.
+    pub os: Option<GuestOsInfo>,
+    /// The timezone of the guest.
+    pub timezone: Option<GuestTimezone>,
+    /// The hostname of the guest.
+    pub hostname: Option<String>,
+    /// The file systems mounted in the guest.
+    pub filesystems: Vec<GuestFilesystem>,
+}
+
+/// A user logged into a guest.
+#[cfg(feature = "libvirt-5-7-0")]
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct GuestUser {
+    /// The name of the user.
+    pub name: String,
+    /// The domain of the user, for guests that have the concept, such as Windows.
+    pub domain: Option<String>,
+    /// When the user logged in.
+    pub login_time: Option<SystemTime>,
+}
+
+/// The operating system running in a guest. The fields correspond to the ones of
+/// `os-release(5)` on Linux.
+#[cfg(feature = "libvirt-5-7-0")]
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct GuestOsInfo {
+    /// Identifier of the operating system, e.g. `fedora`.
+    pub id: Option<String>,
+    /// Name of the operating system, e.g. `Fedora`.
+    pub name: Option<String>,
+    /// Name of the operating system meant for display, e.g. `Fedora 32 (Server Edition)`.
+    pub pretty_name: Option<String>,
+    /// Version of the operating system, e.g. `32 (Server Edition)`.
+    pub version: Option<String>,
+    /// Identifier of the version of the operating system, e.g. `32`.
+    pub version_id: Option<String>,
+    /// Release of the kernel.
+    pub kernel_release: Option<String>,
+    /// Version of the kernel.
+    pub kernel_version: Option<String>,
+    /// Machine hardware name, e.g. `x86_64`.
+    pub machine: Option<String>,
+    /// Variant of the operating system, e.g. `Server Edition`.
+    pub variant: Option<String>,
+    /// Identifier of the variant of the operating system, e.g. `server`.
+    pub variant_id: Option<String>,
+}
+
+/// The timezone of a guest.
+#[cfg(feature = "libvirt-5-7-0")]
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct GuestTimezone {
+    /// The name of the timezone, if the guest reports one.
+    pub name: Option<String>,
+    /// The offset to UTC, in seconds.
+    pub offset: i32,
+}
+
+/// A file system mounted in a guest.
+#[cfg(feature = "libvirt-5-7-0")]
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct GuestFilesystem {
+    /// The path the file system is mounted on.
+    pub mountpoint: String,
+    /// The device name of the file system in the guest, e.g. `sda1`.
+    pub name: String,
+    /// The file system type, e.g. `ext4`.
+    pub fstype: String,
+    /// The size of the file system in bytes.
+    pub total_bytes: Option<u64>,
+    /// The number of bytes used on the file system.
+    pub used_bytes: Option<u64>,
+    /// The disks backing the file system.
+    pub disks: Vec<GuestDisk>,
+}
+
+/// A disk backing a file system in a guest.
+#[cfg(feature = "libvirt-5-7-0")]
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct GuestDisk {
+    /// The alias of the domain disk.
+    pub alias: Option<String>,
+    /// The serial number of the disk.
+    pub serial: Option<String>,
+    /// The device node of the disk in the guest, e.g. `/dev/sda`.
+    pub device: Option<String>,
+}
+
+#[cfg(feature = "libvirt-5-7-0")]
+impl GuestInfo {
+    fn from_typed_params(params: &TypedParams) -> Result<Self, Error> {
+        let string = |name: String| params.get_string(param_key(name).as_bytes());
+
+        let mut users = Vec::new();
+        for i in 0..params.get_uint(b"user.count\0")?.unwrap_or(0) {
+            users.push(GuestUser {
+                name: string(format!("user.{}.name", i))?.unwrap_or_default(),
+                domain: string(format!("user.{}.domain", i))?,
+                login_time: params
+                    .get_ullong(param_key(format!("user.{}.login-time", i)).as_bytes())?
+                    .map(|ms| UNIX_EPOCH + Duration::from_millis(ms)),
+            });
+        }
+
+        let os = GuestOsInfo {
+            id: params.get_string(b"os.id\0")?,
+            name: params.get_string(b"os.name\0")?,
+            pretty_name: params.get_string(b"os.pretty-name\0")?,
+            version: params.get_string(b"os.version\0")?,
+            version_id: params.get_string(b"os.version-id\0")?,
+            kernel_release: params.get_string(b"os.kernel-release\0")?,
+            kernel_version: params.get_string(b"os.kernel-version\0")?,
+            machine: params.get_string(b"os.machine\0")?,
+            variant: params.get_string(b"os.variant\0")?,
+            variant_id: params.get_string(b"os.variant-id\0")?,
+        };
+
+        let timezone = params
+            .get_int(b"timezone.offset\0")?
+            .map(|offset| -> Result<_, Error> {
+                Ok(GuestTimezone {
+                    name: params.get_string(b"timezone.name\0")?,
+                    offset,
+                })
+            })
+            .transpose()?;
+
+        let mut filesystems = Vec::new();
+        for i in 0..params.get_uint(b"fs.count\0")?.unwrap_or(0) {
+            let ullong =
+                |field| params.get_ullong(param_key(format!("fs.{}.{}", i, field)).as_bytes());
+            let mut disks = Vec::new();
+            let disk_count =
+                params.get_uint(param_key(format!("fs.{}.disk.count", i)).as_bytes())?;
+            for j in 0..disk_count.unwrap_or(0) {
+                disks.push(GuestDisk {
+                    alias: string(format!("fs.{}.disk.{}.alias", i, j))?,
+                    serial: string(format!("fs.{}.disk.{}.serial", i, j))?,
+                    device: string(format!("fs.{}.disk.{}.device", i, j))?,
+                });
+            }
+            filesystems.push(GuestFilesystem {
+                mountpoint: string(format!("fs.{}.mountpoint", i))?.unwrap_or_default(),
+                name: string(format!("fs.{}.name", i))?.unwrap_or_default(),
+                fstype: string(format!("fs.{}.fstype", i))?.unwrap_or_default(),
+                total_bytes: ullong("total-bytes")?,
+                used_bytes: ullong("used-bytes")?,
+                disks,
+            });
+        }
+
+        Ok(GuestInfo {
+            users,
+            os: if os == GuestOsInfo::default() {
+                None
+            } else {
+                Some(os)
+            },
+            timezone,
+            hostname: params.get_string(b"hostname\0")?,
+            filesystems,
+        })
+    }
+}
+
+/// Nul terminates a parameter name built at runtime, such as `user.0.name`.
+#[cfg(feature = "libvirt-5-7-0")]
+fn param_key(mut name: String) -> String {
+    name.push('\0');
+    name
+}
+
+/// Overwrites the whole buffer of `secret`, including any spare capacity, with zeroes before
+/// freeing it.
+fn zeroize(mut secret: Vec<u8>) {
+    let buf = secret.as_mut_ptr();
+    for i in 0..secret.capacity() {
+        // Volatile writes, so the compiler can't optimize the zeroing away.
+        unsafe { ptr::write_volatile(buf.add(i), 0) };
+    }
+}
+
+/// The vCPUs of a guest, as seen by the guest agent. Returned by [Domain::guest_vcpus].
+///
+/// Each field is a list of vCPU numbers in libvirt's cpu list format, such as `0-3,6`.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct GuestVcpus {
+    /// All vCPUs known to the guest.
+    pub vcpus: String,
+    /// The vCPUs that are online.
+    pub online: String,
+    /// The vCPUs that can be taken offline.
+    pub offlinable: String,
+}
+
+/// How long to wait for the guest agent to respond. Set with
+/// [Domain::agent_set_response_timeout].
+#[cfg(feature = "libvirt-5-10-0")]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum AgentResponseTimeout {
+    /// Wait for a response forever.
+    Block,
+    /// Use the default timeout of the hypervisor.
+    Default,
+    /// Don't wait for a response, fail right away if the agent has not responded yet.
+    NoWait,
+    /// Wait for the given number of seconds.
+    Seconds(u32),
+}
+
+#[cfg(feature = "libvirt-5-10-0")]
+impl AgentResponseTimeout {
+    fn to_raw(self) -> c_int {
+        match self {
+            AgentResponseTimeout::Block => virt_sys::VIR_DOMAIN_AGENT_RESPONSE_TIMEOUT_BLOCK,
+            AgentResponseTimeout::Default => virt_sys::VIR_DOMAIN_AGENT_RESPONSE_TIMEOUT_DEFAULT,
+            AgentResponseTimeout::NoWait => virt_sys::VIR_DOMAIN_AGENT_RESPONSE_TIMEOUT_NOWAIT,
+            AgentResponseTimeout::Seconds(seconds) => seconds.min(c_int::MAX as u32) as c_int,
+        }
+    }
+}
+
+impl Domain {
+    /// Query information about the guest, using the guest agent. Only the kinds of
+    /// information in `types` are requested, all kinds if it is empty.
+    #[cfg(feature = "libvirt-5-7-0")]
+    pub fn guest_info(&self, types: GuestInfoTypes) -> Result<GuestInfo, Error> {
+        let mut params = ptr::null_mut();
+        let mut nparams = 0;
+        match unsafe {
+            virt_sys::virDomainGetGuestInfo(self.0, types.bits(), &mut params, &mut nparams, 0)
+        } {
+            -1 => Err(Error::from(VirtError::last_virt_error())),
+            _ => GuestInfo::from_typed_params(&unsafe { TypedParams::from_raw(params, nparams) }),
+        }
+    }
+
+    /// Set the password of `user` in the guest, using the guest agent.
+    ///
+    /// `password` is emptied, and both its buffer and the copy made to pass it to libvirt are
+    /// overwritten with zeroes, whether the call succeeds or not. A password containing a nul
+    /// byte is rejected with [Error::InvalidPassword].
+    pub fn set_user_password(
+        &self,
+        user: &str,
+        password: &mut String,
+        flags: SetUserPasswordFlags,
+    ) -> Result<(), Error> {
+        let result = self.set_user_password_raw(user, password.as_bytes(), flags);
+        zeroize(mem::take(password).into_bytes());
+        result
+    }
+
+    fn set_user_password_raw(
+        &self,
+        user: &str,
+        password: &[u8],
+        flags: SetUserPasswordFlags,
+    ) -> Result<(), Error> {
+        let user = CString::new(user).map_err(Error::InvalidName)?;
+        // Not using CString::new, since its error would hold on to a copy of the password.
+        if password.contains(&0) {
+            return Err(Error::InvalidPassword);
+        }
+        let mut secret = Vec::with_capacity(password.len() + 1);
+        secret.extend_from_slice(password);
+        secret.push(0);
+        let ret = unsafe {
+            virt_sys::virDomainSetUserPassword(
+                self.0,
+                user.as_ptr(),
+                secret.as_ptr() as *const c_char,
+                flags.bits(),
+            )
+        };
+        zeroize(secret);
+        match ret {
+            -1 => Err(Error::from(VirtError::last_virt_error())),
+            _ => Ok(()),
+        }
+    }
+
+    /// Returns the current time of the guest clock, using the guest agent.
+    pub fn time(&self) -> Result<SystemTime, VirtError> {
+        let mut seconds = 0;
+        let mut nseconds = 0;
+        match unsafe { virt_sys::virDomainGetTime(self.0, &mut seconds, &mut nseconds, 0) } {
+            -1 => Err(VirtError::last_virt_error()),
+            _ => {
+                let offset = Duration::from_secs(seconds.unsigned_abs());
+                let time = if seconds < 0 {
+                    UNIX_EPOCH - offset
+                } else {
+                    UNIX_EPOCH + offset
+                };
+                Ok(time + Duration::from_nanos(nseconds.into()))
+            }
+        }
+    }
+
+    /// Set the guest clock to `time`, using the guest agent. With [SetTimeFlags::SYNC], `time`
+    /// is ignored and the guest re-reads its clock from the hardware clock instead, which is
+    /// useful after resuming a domain that was suspended for a long time.
+    pub fn set_time(&self, time: SystemTime, flags: SetTimeFlags) -> Result<(), VirtError> {
+        let (seconds, nseconds) = match time.duration_since(UNIX_EPOCH) {
+            Ok(since) => (since.as_secs() as i64, since.subsec_nanos()),
+            Err(e) => {
+                let before = e.duration();
+                match before.subsec_nanos() {
+                    0 => (-(before.as_secs() as i64), 0),
+                    nanos => (-(before.as_secs() as i64) - 1, 1_000_000_000 - nanos),
+                }
+            }
+        };
+        match unsafe { virt_sys::virDomainSetTime(self.0, seconds, nseconds, flags.bits()) } {
+            -1 => Err(VirtError::last_virt_error()),
+            _ => Ok(()),
+        }
+    }
+
+    /// Returns the state of the vCPUs as seen by the guest, using the guest agent.
+    pub fn guest_vcpus(&self) -> Result<GuestVcpus, Error> {
+        let mut params = ptr::null_mut();
+        let mut nparams: c_uint = 0;
+        match unsafe { virt_sys::virDomainGetGuestVcpus(self.0, &mut params, &mut nparams, 0) } {
+            -1 => Err(Error::from(VirtError::last_virt_error())),
+            _ => {
+                let params = unsafe { TypedParams::from_raw(params, nparams as c_int) };
+                Ok(GuestVcpus {
+                    vcpus: params.get_string(b"vcpus\0")?.unwrap_or_default(),
+                    online: params.get_string(b"online\0")?.unwrap_or_default(),
+                    offlinable: params.get_string(b"offlinable\0")?.unwrap_or_default(),
+                })
+            }
+        }
+    }
+
+    /// Take the vCPUs in `cpumap` online or offline inside the guest, using the guest agent.
+    /// `cpumap` is a list of vCPU numbers in libvirt's cpu list format, such as `0-3,6`.
+    ///
+    /// This only changes the state of the vCPUs in the guest OS, see [Domain::set_vcpu] to
+    /// hot(un)plug vCPUs.
+    pub fn set_guest_vcpus(&self, cpumap: &str, online: bool) -> Result<(), Error> {
+        let cpumap = CString::new(cpumap).map_err(Error::InvalidCpuList)?;
+        match unsafe {
+            virt_sys::virDomainSetGuestVcpus(self.0, cpumap.as_ptr(), online as c_int, 0)
+        } {
+            -1 => Err(Error::from(VirtError::last_virt_error())),
+            _ => Ok(()),
+        }
+    }
+
+    /// Hotplug or unplug the vCPUs in `vcpumap`, a list of vCPU numbers in libvirt's cpu list
+    /// format, such as `0-3,6`.
+    pub fn set_vcpu(
+        &self,
+        vcpumap: &str,
+        enable: bool,
+        flags: ModificationImpact,
+    ) -> Result<(), Error> {
+        let vcpumap = CString::new(vcpumap).map_err(Error::InvalidCpuList)?;
+        match unsafe {
+            virt_sys::virDomainSetVcpu(self.0, vcpumap.as_ptr(), enable as c_int, flags.bits())
+        } {
+            -1 => Err(Error::from(VirtError::last_virt_error())),
+            _ => Ok(()),
+        }
+    }
+
+    /// Set how long to wait for the guest agent to respond to commands, for the lifetime of
+    /// the connection to the agent.
+    #[cfg(feature = "libvirt-5-10-0")]
+    pub fn agent_set_response_timeout(
+        &self,
+        timeout: AgentResponseTimeout,
+    ) -> Result<(), VirtError> {
+        match unsafe { virt_sys::virDomainAgentSetResponseTimeout(self.0, timeout.to_raw(), 0) } {
+            -1 => Err(VirtError::last_virt_error()),
+            _ => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,121 @@
+use super::{Domain, ModificationImpact};
+use crate::{typed_params::TypedParams, Error, VirtError};
+use std::ffi::CString;
+
+/// The quality of service settings of a network interface of a domain. Read with
+/// [Domain::interface_bandwidth] and changed with [Domain::set_interface_bandwidth].
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq, Hash)]
+pub struct InterfaceBandwidth {
+    /// Limits on the traffic received by the domain.
+    pub inbound: Bandwidth,
+    /// Limits on the traffic sent by the domain.
+    pub outbound: Bandwidth,
+}
+
+/// Bandwidth limits in one direction of a network interface. `None` values are not reported,
+/// or left unchanged when setting. A value of zero removes the limit.
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq, Hash)]
+pub struct Bandwidth {
+    /// Average rate, in kilobytes per second.
+    pub average: Option<u32>,
+    /// Maximum rate, in kilobytes per second.
+    pub peak: Option<u32>,
+    /// Amount of data that may be sent at peak rate, in kilobytes.
+    pub burst: Option<u32>,
+    /// Guaranteed minimal rate, in kilobytes per second. Only supported for inbound traffic on
+    /// interfaces connected to a libvirt managed network.
+    pub floor: Option<u32>,
+}
+
+impl InterfaceBandwidth {
+    pub(crate) fn from_typed_params(params: &TypedParams) -> Result<Self, VirtError> {
+        Ok(InterfaceBandwidth {
+            inbound: Bandwidth {
+                average: params.get_uint(virt_sys::VIR_DOMAIN_BANDWIDTH_IN_AVERAGE)?,
+                peak: params.get_uint(virt_sys::VIR_DOMAIN_BANDWIDTH_IN_PEAK)?,
+                burst: params.get_uint(virt_sys::VIR_DOMAIN_BANDWIDTH_IN_BURST)?,
+                floor: params.get_uint(virt_sys::VIR_DOMAIN_BANDWIDTH_IN_FLOOR)?,
+            },
+            outbound: Bandwidth {
+                average: params.get_uint(virt_sys::VIR_DOMAIN_BANDWIDTH_OUT_AVERAGE)?,
+                peak: params.get_uint(virt_sys::VIR_DOMAIN_BANDWIDTH_OUT_PEAK)?,
+                burst: params.get_uint(virt_sys::VIR_DOMAIN_BANDWIDTH_OUT_BURST)?,
+                floor: None,
+            },
+        })
+    }
+
+    pub(crate) fn to_typed_params(self) -> Result<TypedParams, Error> {
+        let mut params = TypedParams::new();
+        let values = [
+            (
+                virt_sys::VIR_DOMAIN_BANDWIDTH_IN_AVERAGE as &[u8],
+                self.inbound.average,
+            ),
+            (virt_sys::VIR_DOMAIN_BANDWIDTH_IN_PEAK, self.inbound.peak),
+            (virt_sys::VIR_DOMAIN_BANDWIDTH_IN_BURST, self.inbound.burst),
+            (virt_sys::VIR_DOMAIN_BANDWIDTH_IN_FLOOR, self.inbound.floor),
+            (
+                virt_sys::VIR_DOMAIN_BANDWIDTH_OUT_AVERAGE,
+                self.outbound.average,
+            ),
+            (virt_sys::VIR_DOMAIN_BANDWIDTH_OUT_PEAK, self.outbound.peak),
+            (
+                virt_sys::VIR_DOMAIN_BANDWIDTH_OUT_BURST,
+                self.outbound.burst,
+            ),
+        ];
+        for (name, value) in values.iter() {
+            if let Some(value) = value {
+                params.add_uint(name, *value)?;
+            }
+        }
+        Ok(params)
+    }
+}
+
+impl Domain {
+    /// Returns the bandwidth limits of the network interface `device`, given by its target
+    /// device name or MAC address.
+    pub fn interface_bandwidth(
+        &self,
+        device: &str,
+        flags: ModificationImpact,
+    ) -> Result<InterfaceBandwidth, Error> {
+        let device = CString::new(device).map_err(Error::InvalidName)?;
+        let params = TypedParams::fetch(|params, nparams| unsafe {
+            virt_sys::virDomainGetInterfaceParameters(
+                self.0,
+                device.as_ptr(),
+                params,
+                nparams,
+                flags.bits(),
+            )
+        })?;
+        Ok(InterfaceBandwidth::from_typed_params(&params)?)
+    }
+
+    /// Change the bandwidth limits of the network interface `device`, given by its target
+    /// device name or MAC address. Only the limits that are `Some` are changed.
+    pub fn set_interface_bandwidth(
+        &self,
+        device: &str,
+        bandwidth: &InterfaceBandwidth,
+        flags: ModificationImpact,
+    ) -> Result<(), Error> {
+        let device = CString::new(device).map_err(Error::InvalidName)?;
+        let params = bandwidth.to_typed_params()?;
+        match unsafe {
+            virt_sys::virDomainSetInterfaceParameters(
+                self.0,
+                device.as_ptr(),
+                params.as_ptr(),
+                params.len(),
+                flags.bits(),
+            )
+        } {
+            -1 => Err(Error::from(VirtError::last_virt_error())),
+            _ => Ok(()),
+        }
+    }
+}
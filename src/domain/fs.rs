@@ -0,0 +1,188 @@
+use super::Domain;
+use crate::{
+    util::{copy_opt_string, opt_ptr},
+    Error, VirtError,
+};
+use std::{
+    ffi::CString,
+    mem,
+    os::raw::{c_char, c_uint},
+    ptr, slice,
+};
+
+/// A file system mounted inside the guest, as reported by the guest agent. Values the guest
+/// agent did not report are `None`.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct FsInfo {
+    /// The path the file system is mounted on.
+    pub mountpoint: Option<String>,
+    /// The device name of the file system in the guest, e.g. `sda1`.
+    pub name: Option<String>,
+    /// The file system type, e.g. `ext4`.
+    pub fstype: Option<String>,
+    /// The aliases of the domain disks backing the file system.
+    pub dev_aliases: Vec<String>,
+}
+
+impl FsInfo {
+    /// # Safety
+    ///
+    /// `info` must point to a valid `virDomainFSInfo`.
+    unsafe fn from_raw(info: &virt_sys::virDomainFSInfo) -> Result<Self, Error> {
+        let dev_aliases = if info.devAlias.is_null() {
+            Vec::new()
+        } else {
+            slice::from_raw_parts(info.devAlias, info.ndevAlias)
+                .iter()
+                .filter_map(|&alias| copy_opt_string(alias).transpose())
+                .collect::<Result<_, _>>()?
+        };
+        Ok(FsInfo {
+            mountpoint: copy_opt_string(info.mountpoint)?,
+            name: copy_opt_string(info.name)?,
+            fstype: copy_opt_string(info.fstype)?,
+            dev_aliases,
+        })
+    }
+}
+
+/// Freezes guest file systems for as long as it is alive, and thaws them again when dropped.
+/// Created by [Domain::fs_freeze_guard].
+///
+/// Holding the file systems frozen through a guard ensures they are thawed again, even when
+/// taking a snapshot fails halfway through.
+pub struct FreezeGuard<'a> {
+    domain: &'a Domain,
+    mountpoints: Vec<CString>,
+}
+
+impl FreezeGuard<'_> {
+    /// Thaw the file systems frozen by this guard, returning the number of thawed file
+    /// systems.
+    ///
+    /// If this is not explicitly called it will be called by the `Drop` implementation, and any
+    /// error will be logged to the error level.
+    pub fn thaw(self) -> Result<u32, VirtError> {
+        let result = self.thaw_internal();
+        mem::forget(self);
+        result
+    }
+
+    fn thaw_internal(&self) -> Result<u32, VirtError> {
+        self.domain.fs_thaw_raw(&self.mountpoints)
+    }
+}
+
+impl Drop for FreezeGuard<'_> {
+    fn drop(&mut self) {
+        if let Err(e) = self.thaw_internal() {
+            log::error!("Error when thawing guest file systems: {}", e);
+        }
+    }
+}
+
+fn mountpoint_cstrings(mountpoints: &[&str]) -> Result<Vec<CString>, Error> {
+    mountpoints
+        .iter()
+        .map(|&mountpoint| CString::new(mountpoint).map_err(Error::InvalidPath))
+        .collect()
+}
+
+/// Builds the pointer array passed to `virDomainFSFreeze` and `virDomainFSThaw`. The returned
+/// pointers borrow from `mountpoints`.
+fn mountpoint_ptrs(mountpoints: &[CString]) -> Vec<*const c_char> {
+    mountpoints.iter().map(|m| m.as_ptr()).collect()
+}
+
+impl Domain {
+    /// Freeze the given file systems in the guest, using the guest agent. If `mountpoints` is
+    /// empty, all file systems are frozen. Returns the number of frozen file systems.
+    ///
+    /// Every freeze must be matched with a call to [Domain::fs_thaw], consider using
+    /// [Domain::fs_freeze_guard] instead.
+    pub fn fs_freeze(&self, mountpoints: &[&str]) -> Result<u32, Error> {
+        let mountpoints = mountpoint_cstrings(mountpoints)?;
+        Ok(self.fs_freeze_raw(&mountpoints)?)
+    }
+
+    /// Like [Domain::fs_freeze], but returns a guard that thaws the same file systems when it
+    /// is dropped.
+    pub fn fs_freeze_guard(&self, mountpoints: &[&str]) -> Result<FreezeGuard<'_>, Error> {
+        let mountpoints = mountpoint_cstrings(mountpoints)?;
+        self.fs_freeze_raw(&mountpoints)?;
+        Ok(FreezeGuard {
+            domain: self,
+            mountpoints,
+        })
+    }
+
+    /// Thaw the given file systems in the guest, using the guest agent. If `mountpoints` is
+    /// empty, all file systems are thawed. Returns the number of thawed file systems.
+    pub fn fs_thaw(&self, mountpoints: &[&str]) -> Result<u32, Error> {
+        let mountpoints = mountpoint_cstrings(mountpoints)?;
+        Ok(self.fs_thaw_raw(&mountpoints)?)
+    }
+
+    fn fs_freeze_raw(&self, mountpoints: &[CString]) -> Result<u32, VirtError> {
+        let mut ptrs = mountpoint_ptrs(mountpoints);
+        let ptrs_ptr = if ptrs.is_empty() {
+            ptr::null_mut()
+        } else {
+            ptrs.as_mut_ptr()
+        };
+        match unsafe { virt_sys::virDomainFSFreeze(self.0, ptrs_ptr, ptrs.len() as c_uint, 0) } {
+            -1 => Err(VirtError::last_virt_error()),
+            n => Ok(n as u32),
+        }
+    }
+
+    fn fs_thaw_raw(&self, mountpoints: &[CString]) -> Result<u32, VirtError> {
+        let mut ptrs = mountpoint_ptrs(mountpoints);
+        let ptrs_ptr = if ptrs.is_empty() {
+            ptr::null_mut()
+        } else {
+            ptrs.as_mut_ptr()
+        };
+        match unsafe { virt_sys::virDomainFSThaw(self.0, ptrs_ptr, ptrs.len() as c_uint, 0) } {
+            -1 => Err(VirtError::last_virt_error()),
+            n => Ok(n as u32),
+        }
+    }
+
+    /// Discard unused blocks of a file system in the guest, using the guest agent. If
+    /// `mountpoint` is `None`, all file systems are trimmed. Free ranges smaller than `minimum`
+    /// bytes may be ignored.
+    pub fn fs_trim(&self, mountpoint: Option<&str>, minimum: u64) -> Result<(), Error> {
+        let mountpoint = mountpoint
+            .map(CString::new)
+            .transpose()
+            .map_err(Error::InvalidPath)?;
+        match unsafe { virt_sys::virDomainFSTrim(self.0, opt_ptr(&mountpoint), minimum, 0) } {
+            -1 => Err(Error::from(VirtError::last_virt_error())),
+            _ => Ok(()),
+        }
+    }
+
+    /// Returns the file systems mounted in the guest, using the guest agent.
+    pub fn fs_info(&self) -> Result<Vec<FsInfo>, Error> {
+        let mut infos = ptr::null_mut();
+        let n = match unsafe { virt_sys::virDomainGetFSInfo(self.0, &mut infos, 0) } {
+            -1 => return Err(Error::from(VirtError::last_virt_error())),
+            n => n as usize,
+        };
+        if infos.is_null() {
+            return Ok(Vec::new());
+        }
+        let result = unsafe { slice::from_raw_parts(infos, n) }
+            .iter()
+            .map(|&info| unsafe { FsInfo::from_raw(&*info) })
+            .collect();
+        unsafe {
+            for &info in slice::from_raw_parts(infos, n) {
+                virt_sys::virDomainFSInfoFree(info);
+            }
+            libc::free(infos as *mut _);
+        }
+        result
+    }
+}
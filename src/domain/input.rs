@@ -0,0 +1,171 @@
+use super::Domain;
+use crate::{Error, VirtError};
+use std::os::raw::{c_int, c_uint};
+
+/// The set of keycodes the keys passed to [Domain::send_key] are taken from.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum KeycodeSet {
+    /// Linux input event codes, as used by evdev.
+    Linux,
+    /// IBM PC XT scancodes.
+    Xt,
+    /// IBM PC AT set 1 scancodes.
+    Atset1,
+    /// IBM PC AT set 2 scancodes.
+    Atset2,
+    /// IBM PC AT set 3 scancodes.
+    Atset3,
+    /// Apple OS X virtual keycodes.
+    Osx,
+    /// XT scancodes as used by the Linux XT keyboard driver.
+    XtKbd,
+    /// USB HID keyboard usage codes.
+    Usb,
+    /// Windows virtual key codes.
+    Win32,
+    /// XT scancodes encoded as a single number, as used by QEMU. Also known as the RFB
+    /// keycode set, after its use in the VNC protocol.
+    Qnum,
+}
+
+impl KeycodeSet {
+    fn to_raw(self) -> virt_sys::virKeycodeSet {
+        match self {
+            KeycodeSet::Linux => virt_sys::VIR_KEYCODE_SET_LINUX,
+            KeycodeSet::Xt => virt_sys::VIR_KEYCODE_SET_XT,
+            KeycodeSet::Atset1 => virt_sys::VIR_KEYCODE_SET_ATSET1,
+            KeycodeSet::Atset2 => virt_sys::VIR_KEYCODE_SET_ATSET2,
+            KeycodeSet::Atset3 => virt_sys::VIR_KEYCODE_SET_ATSET3,
+            KeycodeSet::Osx => virt_sys::VIR_KEYCODE_SET_OSX,
+            KeycodeSet::XtKbd => virt_sys::VIR_KEYCODE_SET_XT_KBD,
+            KeycodeSet::Usb => virt_sys::VIR_KEYCODE_SET_USB,
+            KeycodeSet::Win32 => virt_sys::VIR_KEYCODE_SET_WIN32,
+            KeycodeSet::Qnum => virt_sys::VIR_KEYCODE_SET_QNUM,
+        }
+    }
+}
+
+/// A signal to send to a process in the guest with [Domain::send_process_signal]. The numbering
+/// is independent of the signal numbers of the host and guest platforms.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum ProcessSignal {
+    Nop,
+    Hup,
+    Int,
+    Quit,
+    Ill,
+    Trap,
+    Abrt,
+    Bus,
+    Fpe,
+    Kill,
+    Usr1,
+    Segv,
+    Usr2,
+    Pipe,
+    Alrm,
+    Term,
+    Stkflt,
+    Chld,
+    Cont,
+    Stop,
+    Tstp,
+    Ttin,
+    Ttou,
+    Urg,
+    Xcpu,
+    Xfsz,
+    Vtalrm,
+    Prof,
+    Winch,
+    Poll,
+    Pwr,
+    Sys,
+    /// Real-time signal `SIGRTMIN + n`, where `n` is at most 32.
+    Rt(u8),
+}
+
+impl ProcessSignal {
+    /// Returns `None` for real-time signals out of range.
+    fn to_raw(self) -> Option<virt_sys::virDomainProcessSignal> {
+        let raw = match self {
+            ProcessSignal::Nop => virt_sys::VIR_DOMAIN_PROCESS_SIGNAL_NOP,
+            ProcessSignal::Hup => virt_sys::VIR_DOMAIN_PROCESS_SIGNAL_HUP,
+            ProcessSignal::Int => virt_sys::VIR_DOMAIN_PROCESS_SIGNAL_INT,
+            ProcessSignal::Quit => virt_sys::VIR_DOMAIN_PROCESS_SIGNAL_QUIT,
+            ProcessSignal::Ill => virt_sys::VIR_DOMAIN_PROCESS_SIGNAL_ILL,
+            ProcessSignal::Trap => virt_sys::VIR_DOMAIN_PROCESS_SIGNAL_TRAP,
+            ProcessSignal::Abrt => virt_sys::VIR_DOMAIN_PROCESS_SIGNAL_ABRT,
+            ProcessSignal::Bus => virt_sys::VIR_DOMAIN_PROCESS_SIGNAL_BUS,
+            ProcessSignal::Fpe => virt_sys::VIR_DOMAIN_PROCESS_SIGNAL_FPE,
+            ProcessSignal::Kill => virt_sys::VIR_DOMAIN_PROCESS_SIGNAL_KILL,
+            ProcessSignal::Usr1 => virt_sys::VIR_DOMAIN_PROCESS_SIGNAL_USR1,
+            ProcessSignal::Segv => virt_sys::VIR_DOMAIN_PROCESS_SIGNAL_SEGV,
+            ProcessSignal::Usr2 => virt_sys::VIR_DOMAIN_PROCESS_SIGNAL_USR2,
+            ProcessSignal::Pipe => virt_sys::VIR_DOMAIN_PROCESS_SIGNAL_PIPE,
+            ProcessSignal::Alrm => virt_sys::VIR_DOMAIN_PROCESS_SIGNAL_ALRM,
+            ProcessSignal::Term => virt_sys::VIR_DOMAIN_PROCESS_SIGNAL_TERM,
+            ProcessSignal::Stkflt => virt_sys::VIR_DOMAIN_PROCESS_SIGNAL_STKFLT,
+            ProcessSignal::Chld => virt_sys::VIR_DOMAIN_PROCESS_SIGNAL_CHLD,
+            ProcessSignal::Cont => virt_sys::VIR_DOMAIN_PROCESS_SIGNAL_CONT,
+            ProcessSignal::Stop => virt_sys::VIR_DOMAIN_PROCESS_SIGNAL_STOP,
+            ProcessSignal::Tstp => virt_sys::VIR_DOMAIN_PROCESS_SIGNAL_TSTP,
+            ProcessSignal::Ttin => virt_sys::VIR_DOMAIN_PROCESS_SIGNAL_TTIN,
+            ProcessSignal::Ttou => virt_sys::VIR_DOMAIN_PROCESS_SIGNAL_TTOU,
+            ProcessSignal::Urg => virt_sys::VIR_DOMAIN_PROCESS_SIGNAL_URG,
+            ProcessSignal::Xcpu => virt_sys::VIR_DOMAIN_PROCESS_SIGNAL_XCPU,
+            ProcessSignal::Xfsz => virt_sys::VIR_DOMAIN_PROCESS_SIGNAL_XFSZ,
+            ProcessSignal::Vtalrm => virt_sys::VIR_DOMAIN_PROCESS_SIGNAL_VTALRM,
+            ProcessSignal::Prof => virt_sys::VIR_DOMAIN_PROCESS_SIGNAL_PROF,
+            ProcessSignal::Winch => virt_sys::VIR_DOMAIN_PROCESS_SIGNAL_WINCH,
+            ProcessSignal::Poll => virt_sys::VIR_DOMAIN_PROCESS_SIGNAL_POLL,
+            ProcessSignal::Pwr => virt_sys::VIR_DOMAIN_PROCESS_SIGNAL_PWR,
+            ProcessSignal::Sys => virt_sys::VIR_DOMAIN_PROCESS_SIGNAL_SYS,
+            ProcessSignal::Rt(n) if n <= 32 => {
+                virt_sys::VIR_DOMAIN_PROCESS_SIGNAL_RT0 + virt_sys::virDomainProcessSignal::from(n)
+            }
+            ProcessSignal::Rt(_) => return None,
+        };
+        Some(raw)
+    }
+}
+
+impl Domain {
+    /// Send key presses to the guest. The keys in `keycodes` are pressed together, held for
+    /// `holdtime_ms` milliseconds, and released. At most
+    /// [virt_sys::VIR_DOMAIN_SEND_KEY_MAX_KEYS] keys can be sent at once.
+    ///
+    /// To send Ctrl-Alt-Del: `domain.send_key(KeycodeSet::Linux, 0, &[29, 56, 111])`.
+    pub fn send_key(
+        &self,
+        codeset: KeycodeSet,
+        holdtime_ms: u32,
+        keycodes: &[u32],
+    ) -> Result<(), VirtError> {
+        let mut keycodes = keycodes.to_vec();
+        match unsafe {
+            virt_sys::virDomainSendKey(
+                self.0,
+                codeset.to_raw(),
+                holdtime_ms,
+                keycodes.as_mut_ptr() as *mut c_uint,
+                keycodes.len() as c_int,
+                0,
+            )
+        } {
+            -1 => Err(VirtError::last_virt_error()),
+            _ => Ok(()),
+        }
+    }
+
+    /// Send `signal` to the process with the given PID in the guest. For container based
+    /// hypervisors, PID 1 is the init process of the container. Fails with
+    /// [Error::InvalidSignal] if `signal` is a real-time signal out of range.
+    pub fn send_process_signal(&self, pid: i64, signal: ProcessSignal) -> Result<(), Error> {
+        let signal = signal.to_raw().ok_or(Error::InvalidSignal)?;
+        match unsafe { virt_sys::virDomainSendProcessSignal(self.0, pid, signal, 0) } {
+            -1 => Err(Error::from(VirtError::last_virt_error())),
+            _ => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,113 @@
+use super::Domain;
+use crate::{
+    util::{copy_opt_string, copy_string},
+    Error, VirtError,
+};
+use std::{fmt, net::IpAddr, ptr, slice};
+
+/// Where [Domain::interface_addresses] gets the addresses of the guest interfaces from.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum InterfaceAddressesSource {
+    /// The DHCP leases handed out by libvirt managed networks.
+    Lease,
+    /// The guest agent.
+    Agent,
+    /// The ARP table of the host.
+    Arp,
+}
+
+impl InterfaceAddressesSource {
+    fn to_raw(self) -> virt_sys::virDomainInterfaceAddressesSource {
+        match self {
+            InterfaceAddressesSource::Lease => virt_sys::VIR_DOMAIN_INTERFACE_ADDRESSES_SRC_LEASE,
+            InterfaceAddressesSource::Agent => virt_sys::VIR_DOMAIN_INTERFACE_ADDRESSES_SRC_AGENT,
+            InterfaceAddressesSource::Arp => virt_sys::VIR_DOMAIN_INTERFACE_ADDRESSES_SRC_ARP,
+        }
+    }
+}
+
+/// An IP address together with the prefix length of its network, such as `192.168.122.10/24`.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct IpNet {
+    /// The address.
+    pub addr: IpAddr,
+    /// The number of leading bits of the address that identify the network.
+    pub prefix: u8,
+}
+
+impl fmt::Display for IpNet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.addr, self.prefix)
+    }
+}
+
+/// A network interface of a guest, and the addresses assigned to it.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct GuestInterface {
+    /// The name of the interface. Depending on the source this is either the name inside the
+    /// guest or the name of the tap device on the host.
+    pub name: String,
+    /// The MAC address of the interface.
+    pub hwaddr: Option<String>,
+    /// The IP addresses assigned to the interface.
+    pub addrs: Vec<IpNet>,
+}
+
+impl GuestInterface {
+    /// # Safety
+    ///
+    /// `iface` must point to a valid `virDomainInterface`.
+    unsafe fn from_raw(iface: &virt_sys::virDomainInterface) -> Result<Self, Error> {
+        let addrs = if iface.addrs.is_null() {
+            &[]
+        } else {
+            slice::from_raw_parts(iface.addrs, iface.naddrs as usize)
+        };
+        Ok(GuestInterface {
+            name: copy_string(iface.name)?,
+            hwaddr: copy_opt_string(iface.hwaddr)?,
+            addrs: addrs
+                .iter()
+                .map(|addr| {
+                    Ok(IpNet {
+                        addr: copy_string(addr.addr)?
+                            .parse()
+                            .map_err(Error::InvalidAddress)?,
+                        prefix: addr.prefix as u8,
+                    })
+                })
+                .collect::<Result<_, Error>>()?,
+        })
+    }
+}
+
+impl Domain {
+    /// Returns the network interfaces of the guest and their IP addresses, as known to
+    /// `source`.
+    pub fn interface_addresses(
+        &self,
+        source: InterfaceAddressesSource,
+    ) -> Result<Vec<GuestInterface>, Error> {
+        let mut ifaces = ptr::null_mut();
+        let n = match unsafe {
+            virt_sys::virDomainInterfaceAddresses(self.0, &mut ifaces, source.to_raw(), 0)
+        } {
+            -1 => return Err(Error::from(VirtError::last_virt_error())),
+            n => n as usize,
+        };
+        if ifaces.is_null() {
+            return Ok(Vec::new());
+        }
+        let result = unsafe { slice::from_raw_parts(ifaces, n) }
+            .iter()
+            .map(|&iface| unsafe { GuestInterface::from_raw(&*iface) })
+            .collect();
+        unsafe {
+            for &iface in slice::from_raw_parts(ifaces, n) {
+                virt_sys::virDomainInterfaceFree(iface);
+            }
+            libc::free(ifaces as *mut _);
+        }
+        result
+    }
+}
@@ -0,0 +1,296 @@
+use super::Domain;
+use crate::{typed_params::TypedParams, VirtError};
+use std::{mem, os::raw::c_int, ptr, time::Duration};
+
+bitflags::bitflags! {
+    /// Flags affecting [Domain::job_stats].
+    pub struct JobStatsFlags: u32 {
+        /// Return the statistics of the most recently completed job, instead of the running one.
+        const COMPLETED = virt_sys::VIR_DOMAIN_JOB_STATS_COMPLETED;
+        /// Don't remove the statistics of the completed job after returning them. Only valid
+        /// together with [JobStatsFlags::COMPLETED].
+        #[cfg(feature = "libvirt-6-0-0")]
+        const KEEP_COMPLETED = virt_sys::VIR_DOMAIN_JOB_STATS_KEEP_COMPLETED;
+    }
+}
+
+/// The type of a background job running on a domain.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum JobType {
+    /// No job is active.
+    None,
+    /// A job with a finite completion time.
+    Bounded,
+    /// A job without a finite completion time.
+    Unbounded,
+    /// The job has finished, but isn't cleaned up.
+    Completed,
+    /// The job hit an error, but isn't cleaned up.
+    Failed,
+    /// The job was aborted, but isn't cleaned up.
+    Cancelled,
+    /// A job type not known to this library.
+    Unknown,
+}
+
+impl JobType {
+    fn from_raw(job_type: c_int) -> Self {
+        match job_type as virt_sys::virDomainJobType {
+            virt_sys::VIR_DOMAIN_JOB_NONE => JobType::None,
+            virt_sys::VIR_DOMAIN_JOB_BOUNDED => JobType::Bounded,
+            virt_sys::VIR_DOMAIN_JOB_UNBOUNDED => JobType::Unbounded,
+            virt_sys::VIR_DOMAIN_JOB_COMPLETED => JobType::Completed,
+            virt_sys::VIR_DOMAIN_JOB_FAILED => JobType::Failed,
+            virt_sys::VIR_DOMAIN_JOB_CANCELLED => JobType::Cancelled,
+            _ => JobType::Unknown,
+        }
+    }
+}
+
+/// The operation a background job is performing.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum JobOperation {
+    /// The operation is not known, or was not reported.
+    Unknown,
+    /// Starting the domain.
+    Start,
+    /// Saving the domain state to a file.
+    Save,
+    /// Restoring the domain from a saved state.
+    Restore,
+    /// Receiving an incoming migration.
+    MigrationIn,
+    /// Migrating the domain to another host.
+    MigrationOut,
+    /// Taking a snapshot.
+    Snapshot,
+    /// Reverting the domain to a snapshot.
+    SnapshotRevert,
+    /// Dumping the memory of the domain to a file.
+    Dump,
+    /// Running a backup.
+    #[cfg(feature = "libvirt-6-0-0")]
+    Backup,
+}
+
+impl JobOperation {
+    fn from_raw(operation: c_int) -> Self {
+        match operation as virt_sys::virDomainJobOperation {
+            virt_sys::VIR_DOMAIN_JOB_OPERATION_START => JobOperation::Start,
+            virt_sys::VIR_DOMAIN_JOB_OPERATION_SAVE => JobOperation::Save,
+            virt_sys::VIR_DOMAIN_JOB_OPERATION_RESTORE => JobOperation::Restore,
+            virt_sys::VIR_DOMAIN_JOB_OPERATION_MIGRATION_IN => JobOperation::MigrationIn,
+            virt_sys::VIR_DOMAIN_JOB_OPERATION_MIGRATION_OUT => JobOperation::MigrationOut,
+            virt_sys::VIR_DOMAIN_JOB_OPERATION_SNAPSHOT => JobOperation::Snapshot,
+            virt_sys::VIR_DOMAIN_JOB_OPERATION_SNAPSHOT_REVERT => JobOperation::SnapshotRevert,
+            virt_sys::VIR_DOMAIN_JOB_OPERATION_DUMP => JobOperation::Dump,
+            #[cfg(feature = "libvirt-6-0-0")]
+            virt_sys::VIR_DOMAIN_JOB_OPERATION_BACKUP => JobOperation::Backup,
+            _ => JobOperation::Unknown,
+        }
+    }
+}
+
+/// Progress information about a background job, such as a migration, save or dump, running on
+/// a domain. Statistics the hypervisor did not report are `None`. Sizes are in bytes.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct JobStats {
+    /// The type of the job.
+    pub job_type: JobType,
+    /// The operation the job is performing.
+    pub operation: JobOperation,
+    /// Whether these are the statistics of a completed job, see [JobStatsFlags::COMPLETED].
+    pub completed: bool,
+    /// Time since the start of the job.
+    pub time_elapsed: Option<Duration>,
+    /// Estimated time until the job completes.
+    pub time_remaining: Option<Duration>,
+    /// Time the domain was paused at the end of a migration.
+    pub downtime: Option<Duration>,
+    /// Time spent preparing a migration, before transferring any data.
+    pub setup_time: Option<Duration>,
+    /// The total amount of data the job involves, memory and disk combined.
+    pub data_total: Option<u64>,
+    /// The amount of data processed so far, memory and disk combined.
+    pub data_processed: Option<u64>,
+    /// The amount of data left to process, memory and disk combined.
+    pub data_remaining: Option<u64>,
+    /// The amount of guest memory the job involves.
+    pub memory_total: Option<u64>,
+    /// The amount of guest memory transferred so far.
+    pub memory_processed: Option<u64>,
+    /// The amount of guest memory left to transfer.
+    pub memory_remaining: Option<u64>,
+    /// Number of memory pages filled with a constant byte, transferred without their content.
+    pub memory_constant: Option<u64>,
+    /// Number of memory pages transferred without compression.
+    pub memory_normal: Option<u64>,
+    /// The amount of memory transferred without compression.
+    pub memory_normal_bytes: Option<u64>,
+    /// Memory transfer rate, in bytes per second.
+    pub memory_bps: Option<u64>,
+    /// Number of memory pages dirtied by the guest per second.
+    pub memory_dirty_rate: Option<u64>,
+    /// Size of a memory page on the source host.
+    pub memory_page_size: Option<u64>,
+    /// Number of times memory has been iterated over during a pre-copy migration.
+    pub memory_iteration: Option<u64>,
+    /// Number of page requests from the destination host during a post-copy migration.
+    pub memory_postcopy_requests: Option<u64>,
+    /// The amount of disk data the job involves.
+    pub disk_total: Option<u64>,
+    /// The amount of disk data transferred so far.
+    pub disk_processed: Option<u64>,
+    /// The amount of disk data left to transfer.
+    pub disk_remaining: Option<u64>,
+    /// Disk transfer rate, in bytes per second.
+    pub disk_bps: Option<u64>,
+    /// Size of the compression cache.
+    pub compression_cache: Option<u64>,
+    /// Number of compressed bytes transferred.
+    pub compression_bytes: Option<u64>,
+    /// Number of compressed pages transferred.
+    pub compression_pages: Option<u64>,
+    /// Number of pages not found in the compression cache.
+    pub compression_cache_misses: Option<u64>,
+    /// Number of pages that could not be compressed efficiently.
+    pub compression_overflow: Option<u64>,
+    /// Percentage the domain's CPUs are throttled by to make a migration converge.
+    pub auto_converge_throttle: Option<i32>,
+}
+
+impl JobStats {
+    fn from_typed_params(
+        job_type: c_int,
+        params: &TypedParams,
+        completed: bool,
+    ) -> Result<Self, VirtError> {
+        let ms = |name| {
+            params
+                .get_ullong(name)
+                .map(|value| value.map(Duration::from_millis))
+        };
+        let ullong = |name| params.get_ullong(name);
+        Ok(JobStats {
+            job_type: JobType::from_raw(job_type),
+            operation: params
+                .get_int(virt_sys::VIR_DOMAIN_JOB_OPERATION)?
+                .map_or(JobOperation::Unknown, JobOperation::from_raw),
+            completed,
+            time_elapsed: ms(virt_sys::VIR_DOMAIN_JOB_TIME_ELAPSED)?,
+            time_remaining: ms(virt_sys::VIR_DOMAIN_JOB_TIME_REMAINING)?,
+            downtime: ms(virt_sys::VIR_DOMAIN_JOB_DOWNTIME)?,
+            setup_time: ms(virt_sys::VIR_DOMAIN_JOB_SETUP_TIME)?,
+            data_total: ullong(virt_sys::VIR_DOMAIN_JOB_DATA_TOTAL)?,
+            data_processed: ullong(virt_sys::VIR_DOMAIN_JOB_DATA_PROCESSED)?,
+            data_remaining: ullong(virt_sys::VIR_DOMAIN_JOB_DATA_REMAINING)?,
+            memory_total: ullong(virt_sys::VIR_DOMAIN_JOB_MEMORY_TOTAL)?,
+            memory_processed: ullong(virt_sys::VIR_DOMAIN_JOB_MEMORY_PROCESSED)?,
+            memory_remaining: ullong(virt_sys::VIR_DOMAIN_JOB_MEMORY_REMAINING)?,
+            memory_constant: ullong(virt_sys::VIR_DOMAIN_JOB_MEMORY_CONSTANT)?,
+            memory_normal: ullong(virt_sys::VIR_DOMAIN_JOB_MEMORY_NORMAL)?,
+            memory_normal_bytes: ullong(virt_sys::VIR_DOMAIN_JOB_MEMORY_NORMAL_BYTES)?,
+            memory_bps: ullong(virt_sys::VIR_DOMAIN_JOB_MEMORY_BPS)?,
+            memory_dirty_rate: ullong(virt_sys::VIR_DOMAIN_JOB_MEMORY_DIRTY_RATE)?,
+            memory_page_size: ullong(virt_sys::VIR_DOMAIN_JOB_MEMORY_PAGE_SIZE)?,
+            memory_iteration: ullong(virt_sys::VIR_DOMAIN_JOB_MEMORY_ITERATION)?,
+            memory_postcopy_requests: ullong(virt_sys::VIR_DOMAIN_JOB_MEMORY_POSTCOPY_REQS)?,
+            disk_total: ullong(virt_sys::VIR_DOMAIN_JOB_DISK_TOTAL)?,
+            disk_processed: ullong(virt_sys::VIR_DOMAIN_JOB_DISK_PROCESSED)?,
+            disk_remaining: ullong(virt_sys::VIR_DOMAIN_JOB_DISK_REMAINING)?,
+            disk_bps: ullong(virt_sys::VIR_DOMAIN_JOB_DISK_BPS)?,
+            compression_cache: ullong(virt_sys::VIR_DOMAIN_JOB_COMPRESSION_CACHE)?,
+            compression_bytes: ullong(virt_sys::VIR_DOMAIN_JOB_COMPRESSION_BYTES)?,
+            compression_pages: ullong(virt_sys::VIR_DOMAIN_JOB_COMPRESSION_PAGES)?,
+            compression_cache_misses: ullong(virt_sys::VIR_DOMAIN_JOB_COMPRESSION_CACHE_MISSES)?,
+            compression_overflow: ullong(virt_sys::VIR_DOMAIN_JOB_COMPRESSION_OVERFLOW)?,
+            auto_converge_throttle: params
+                .get_int(virt_sys::VIR_DOMAIN_JOB_AUTO_CONVERGE_THROTTLE)?,
+        })
+    }
+
+    fn from_job_info(info: &virt_sys::virDomainJobInfo) -> Self {
+        let job_type = JobType::from_raw(info.type_);
+        // The counters are left at zero when no job is active, rather than being meaningful.
+        let active = job_type != JobType::None;
+        let ms = |value| active.then_some(Duration::from_millis(value));
+        let counter = |value| active.then_some(value);
+        JobStats {
+            job_type,
+            operation: JobOperation::Unknown,
+            completed: false,
+            time_elapsed: ms(info.timeElapsed),
+            time_remaining: ms(info.timeRemaining),
+            downtime: None,
+            setup_time: None,
+            data_total: counter(info.dataTotal),
+            data_processed: counter(info.dataProcessed),
+            data_remaining: counter(info.dataRemaining),
+            memory_total: counter(info.memTotal),
+            memory_processed: counter(info.memProcessed),
+            memory_remaining: counter(info.memRemaining),
+            memory_constant: None,
+            memory_normal: None,
+            memory_normal_bytes: None,
+            memory_bps: None,
+            memory_dirty_rate: None,
+            memory_page_size: None,
+            memory_iteration: None,
+            memory_postcopy_requests: None,
+            disk_total: counter(info.fileTotal),
+            disk_processed: counter(info.fileProcessed),
+            disk_remaining: counter(info.fileRemaining),
+            disk_bps: None,
+            compression_cache: None,
+            compression_bytes: None,
+            compression_pages: None,
+            compression_cache_misses: None,
+            compression_overflow: None,
+            auto_converge_throttle: None,
+        }
+    }
+}
+
+impl Domain {
+    /// Returns basic progress information about the background job running on the domain.
+    /// The statistics are all `None` when no job is active. [Domain::job_stats] returns more
+    /// detailed information, when supported by the hypervisor.
+    pub fn job_info(&self) -> Result<JobStats, VirtError> {
+        let mut info: virt_sys::virDomainJobInfo = unsafe { mem::zeroed() };
+        match unsafe { virt_sys::virDomainGetJobInfo(self.0, &mut info) } {
+            -1 => Err(VirtError::last_virt_error()),
+            _ => Ok(JobStats::from_job_info(&info)),
+        }
+    }
+
+    /// Returns detailed progress information about the background job running on the domain,
+    /// or with [JobStatsFlags::COMPLETED], about the most recently completed job.
+    pub fn job_stats(&self, flags: JobStatsFlags) -> Result<JobStats, VirtError> {
+        let mut job_type: c_int = 0;
+        let mut params_ptr = ptr::null_mut();
+        let mut nparams: c_int = 0;
+        let ret = unsafe {
+            virt_sys::virDomainGetJobStats(
+                self.0,
+                &mut job_type,
+                &mut params_ptr,
+                &mut nparams,
+                flags.bits(),
+            )
+        };
+        if ret == -1 {
+            return Err(VirtError::last_virt_error());
+        }
+        let params = unsafe { TypedParams::from_raw(params_ptr, nparams) };
+        JobStats::from_typed_params(job_type, &params, flags.contains(JobStatsFlags::COMPLETED))
+    }
+
+    /// Abort the background job currently running on the domain, such as a migration, save or
+    /// dump. The call that started the job will return an error.
+    pub fn abort_job(&self) -> Result<(), VirtError> {
+        match unsafe { virt_sys::virDomainAbortJob(self.0) } {
+            -1 => Err(VirtError::last_virt_error()),
+            _ => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,91 @@
+use super::{Domain, ModificationImpact};
+use crate::{
+    util::{opt_ptr, take_string, xml_cstring},
+    Error, VirtError,
+};
+use std::{ffi::CString, os::raw::c_int};
+
+/// The kind of metadata accessed by [Domain::metadata] and [Domain::set_metadata].
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum MetadataKind {
+    /// The free form `<description>` of the domain.
+    Description,
+    /// The short `<title>` of the domain.
+    Title,
+    /// A custom XML element in `<metadata>`, identified by its namespace URI.
+    Element,
+}
+
+impl MetadataKind {
+    fn to_raw(self) -> c_int {
+        let kind = match self {
+            MetadataKind::Description => virt_sys::VIR_DOMAIN_METADATA_DESCRIPTION,
+            MetadataKind::Title => virt_sys::VIR_DOMAIN_METADATA_TITLE,
+            MetadataKind::Element => virt_sys::VIR_DOMAIN_METADATA_ELEMENT,
+        };
+        kind as c_int
+    }
+}
+
+impl Domain {
+    /// Returns the metadata of the given kind, or `None` if the domain has none.
+    ///
+    /// For [MetadataKind::Element], `uri` is the namespace URI of the element, and the element
+    /// is returned as an XML document. It must be `None` for the other kinds.
+    pub fn metadata(
+        &self,
+        kind: MetadataKind,
+        uri: Option<&str>,
+        flags: ModificationImpact,
+    ) -> Result<Option<String>, Error> {
+        let uri = uri
+            .map(CString::new)
+            .transpose()
+            .map_err(Error::InvalidUri)?;
+        match cvt_null!(unsafe {
+            virt_sys::virDomainGetMetadata(self.0, kind.to_raw(), opt_ptr(&uri), flags.bits())
+        }) {
+            Ok(metadata) => Ok(Some(unsafe { take_string(metadata) }?)),
+            Err(e) if e.is_code(virt_sys::VIR_ERR_NO_DOMAIN_METADATA) => Ok(None),
+            Err(e) => Err(Error::from(e)),
+        }
+    }
+
+    /// Set the metadata of the given kind, or remove it if `value` is `None`.
+    ///
+    /// For [MetadataKind::Element], `value` is an XML document with a single root element,
+    /// which is stored in the namespace `uri` using the namespace prefix `key`. Any existing
+    /// element in the same namespace is replaced. `key` and `uri` must be `None` for the other
+    /// kinds.
+    pub fn set_metadata(
+        &self,
+        kind: MetadataKind,
+        value: Option<&str>,
+        key: Option<&str>,
+        uri: Option<&str>,
+        flags: ModificationImpact,
+    ) -> Result<(), Error> {
+        let value = xml_cstring(value)?;
+        let key = key
+            .map(CString::new)
+            .transpose()
+            .map_err(Error::InvalidName)?;
+        let uri = uri
+            .map(CString::new)
+            .transpose()
+            .map_err(Error::InvalidUri)?;
+        match unsafe {
+            virt_sys::virDomainSetMetadata(
+                self.0,
+                kind.to_raw(),
+                opt_ptr(&value),
+                opt_ptr(&key),
+                opt_ptr(&uri),
+                flags.bits(),
+            )
+        } {
+            -1 => Err(Error::from(VirtError::last_virt_error())),
+            _ => Ok(()),
+        }
+    }
+}
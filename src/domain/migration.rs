@@ -0,0 +1,476 @@
+use super::Domain;
+use crate::{typed_params::TypedParams, util::opt_ptr, Connection, Error, VirtError, Wrapper};
+use std::{
+    ffi::{CStr, CString},
+    os::raw::c_ulong,
+};
+
+bitflags::bitflags! {
+    /// Flags affecting how a domain is migrated with [Domain::migrate] and
+    /// [Domain::migrate_to_uri].
+    pub struct MigrateFlags: u32 {
+        /// Do not pause the domain during migration. The domain's memory is transferred while
+        /// it keeps running.
+        const LIVE = virt_sys::VIR_MIGRATE_LIVE;
+        /// Let the source libvirt daemon connect directly to the destination daemon, instead
+        /// of the client managing the migration.
+        const PEER2PEER = virt_sys::VIR_MIGRATE_PEER2PEER;
+        /// Tunnel the migration data over the libvirt RPC channel. Requires
+        /// [MigrateFlags::PEER2PEER].
+        const TUNNELLED = virt_sys::VIR_MIGRATE_TUNNELLED;
+        /// Define the domain as persistent on the destination host.
+        const PERSIST_DEST = virt_sys::VIR_MIGRATE_PERSIST_DEST;
+        /// Undefine the domain on the source host once the migration succeeds.
+        const UNDEFINE_SOURCE = virt_sys::VIR_MIGRATE_UNDEFINE_SOURCE;
+        /// Leave the domain suspended on the destination host.
+        const PAUSED = virt_sys::VIR_MIGRATE_PAUSED;
+        /// Migrate full disk images in addition to the domain's memory.
+        const NON_SHARED_DISK = virt_sys::VIR_MIGRATE_NON_SHARED_DISK;
+        /// Migrate disk images incrementally, assuming the same base image is available on the
+        /// destination host.
+        const NON_SHARED_INC = virt_sys::VIR_MIGRATE_NON_SHARED_INC;
+        /// Protect the domain from configuration changes during the migration.
+        const CHANGE_PROTECTION = virt_sys::VIR_MIGRATE_CHANGE_PROTECTION;
+        /// Force the migration even if libvirt considers it unsafe.
+        const UNSAFE = virt_sys::VIR_MIGRATE_UNSAFE;
+        /// Migrate only the domain definition, without its running state.
+        const OFFLINE = virt_sys::VIR_MIGRATE_OFFLINE;
+        /// Compress the migration data. See [MigrateParams::compression].
+        const COMPRESSED = virt_sys::VIR_MIGRATE_COMPRESSED;
+        /// Abort the migration on I/O errors during disk migration.
+        const ABORT_ON_ERROR = virt_sys::VIR_MIGRATE_ABORT_ON_ERROR;
+        /// Slow down the domain's CPUs if the migration is not converging.
+        const AUTO_CONVERGE = virt_sys::VIR_MIGRATE_AUTO_CONVERGE;
+        /// Pin all the domain's memory when using RDMA migration.
+        const RDMA_PIN_ALL = virt_sys::VIR_MIGRATE_RDMA_PIN_ALL;
+        /// Allow switching the migration to post-copy mode once it is running. The switch itself
+        /// is made with [Domain::migrate_start_post_copy].
+        const POSTCOPY = virt_sys::VIR_MIGRATE_POSTCOPY;
+        /// Encrypt the migration data with TLS.
+        const TLS = virt_sys::VIR_MIGRATE_TLS;
+        /// Send the migration data over multiple parallel connections. See
+        /// [MigrateParams::parallel_connections].
+        #[cfg(feature = "libvirt-5-2-0")]
+        const PARALLEL = virt_sys::VIR_MIGRATE_PARALLEL;
+    }
+}
+
+bitflags::bitflags! {
+    /// Flags affecting [Domain::migrate_set_max_speed] and [Domain::migrate_max_speed].
+    pub struct MigrateMaxSpeedFlags: u32 {
+        /// Operate on the bandwidth limit of the post-copy phase of the migration, instead of
+        /// the pre-copy phase.
+        #[cfg(feature = "libvirt-5-1-0")]
+        const POSTCOPY = virt_sys::VIR_DOMAIN_MIGRATE_MAX_SPEED_POSTCOPY;
+    }
+}
+
+/// A compression method for migration data, used with [MigrateFlags::COMPRESSED].
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum MigrateCompression {
+    /// XOR based run length encoding, only sending the changes of memory pages.
+    Xbzrle,
+    /// Multithreaded compression of memory pages.
+    Mt,
+}
+
+impl MigrateCompression {
+    fn as_cstr(self) -> &'static CStr {
+        let name: &'static [u8] = match self {
+            MigrateCompression::Xbzrle => b"xbzrle\0",
+            MigrateCompression::Mt => b"mt\0",
+        };
+        CStr::from_bytes_with_nul(name).unwrap()
+    }
+}
+
+/// Typed parameters for [Domain::migrate] and [Domain::migrate_to_uri]. Parameters that are
+/// not set are left for libvirt to decide.
+#[derive(Debug, Default, Clone)]
+pub struct MigrateParams {
+    uri: Option<CString>,
+    dest_name: Option<CString>,
+    dest_xml: Option<CString>,
+    persist_xml: Option<CString>,
+    bandwidth: Option<u64>,
+    #[cfg(feature = "libvirt-5-1-0")]
+    bandwidth_postcopy: Option<u64>,
+    graphics_uri: Option<CString>,
+    listen_address: Option<CString>,
+    migrate_disks: Vec<CString>,
+    disks_port: Option<i32>,
+    compression: Vec<MigrateCompression>,
+    compression_mt_level: Option<i32>,
+    compression_mt_threads: Option<i32>,
+    compression_mt_dthreads: Option<i32>,
+    compression_xbzrle_cache: Option<u64>,
+    auto_converge_initial: Option<i32>,
+    auto_converge_increment: Option<i32>,
+    #[cfg(feature = "libvirt-5-2-0")]
+    parallel_connections: Option<i32>,
+    #[cfg(feature = "libvirt-6-0-0")]
+    tls_destination: Option<CString>,
+}
+
+impl MigrateParams {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the URI to use for the migration data stream, as understood by the hypervisor.
+    /// Usually not needed, the hypervisor picks one by itself.
+    pub fn uri(&mut self, uri: &str) -> Result<&mut Self, Error> {
+        self.uri = Some(CString::new(uri).map_err(Error::InvalidUri)?);
+        Ok(self)
+    }
+
+    /// Sets the name the domain should have on the destination host.
+    pub fn dest_name(&mut self, name: &str) -> Result<&mut Self, Error> {
+        self.dest_name = Some(CString::new(name).map_err(Error::InvalidName)?);
+        Ok(self)
+    }
+
+    /// Sets the domain XML to use on the destination host. It must only change host-specific
+    /// portions of the domain, such as disk paths.
+    pub fn dest_xml(&mut self, xml: &str) -> Result<&mut Self, Error> {
+        self.dest_xml = Some(CString::new(xml).map_err(Error::InvalidXml)?);
+        Ok(self)
+    }
+
+    /// Sets the persistent domain XML to define on the destination host, when using
+    /// [MigrateFlags::PERSIST_DEST].
+    pub fn persist_xml(&mut self, xml: &str) -> Result<&mut Self, Error> {
+        self.persist_xml = Some(CString::new(xml).map_err(Error::InvalidXml)?);
+        Ok(self)
+    }
+
+    /// Sets the maximum bandwidth, in MiB/s, the migration may use.
+    pub fn bandwidth(&mut self, bandwidth: u64) -> &mut Self {
+        self.bandwidth = Some(bandwidth);
+        self
+    }
+
+    /// Sets the maximum bandwidth, in MiB/s, the migration may use in post-copy mode.
+    #[cfg(feature = "libvirt-5-1-0")]
+    pub fn bandwidth_postcopy(&mut self, bandwidth: u64) -> &mut Self {
+        self.bandwidth_postcopy = Some(bandwidth);
+        self
+    }
+
+    /// Sets the URI the graphics clients of the domain should automatically reconnect to
+    /// once the domain has been migrated.
+    pub fn graphics_uri(&mut self, uri: &str) -> Result<&mut Self, Error> {
+        self.graphics_uri = Some(CString::new(uri).map_err(Error::InvalidUri)?);
+        Ok(self)
+    }
+
+    /// Sets the address the hypervisor on the destination host should listen on for the
+    /// incoming migration, as an IP address or a hostname.
+    pub fn listen_address(&mut self, address: &str) -> Result<&mut Self, Error> {
+        self.listen_address = Some(CString::new(address).map_err(Error::InvalidName)?);
+        Ok(self)
+    }
+
+    /// Adds a disk, by its target name such as `vda`, to migrate with
+    /// [MigrateFlags::NON_SHARED_DISK] or [MigrateFlags::NON_SHARED_INC]. If no disks are
+    /// added, all non-shared disks are migrated.
+    pub fn migrate_disk(&mut self, target: &str) -> Result<&mut Self, Error> {
+        self.migrate_disks
+            .push(CString::new(target).map_err(Error::InvalidName)?);
+        Ok(self)
+    }
+
+    /// Sets the port the destination host should listen on for the disk migration.
+    pub fn disks_port(&mut self, port: u16) -> &mut Self {
+        self.disks_port = Some(i32::from(port));
+        self
+    }
+
+    /// Adds a compression method to use with [MigrateFlags::COMPRESSED]. Multiple methods
+    /// can be combined.
+    pub fn compression(&mut self, method: MigrateCompression) -> &mut Self {
+        self.compression.push(method);
+        self
+    }
+
+    /// Sets the compression level, from 0 to 9, of [MigrateCompression::Mt].
+    pub fn compression_mt_level(&mut self, level: i32) -> &mut Self {
+        self.compression_mt_level = Some(level);
+        self
+    }
+
+    /// Sets the number of compression threads on the source host for
+    /// [MigrateCompression::Mt].
+    pub fn compression_mt_threads(&mut self, threads: i32) -> &mut Self {
+        self.compression_mt_threads = Some(threads);
+        self
+    }
+
+    /// Sets the number of decompression threads on the destination host for
+    /// [MigrateCompression::Mt].
+    pub fn compression_mt_dthreads(&mut self, threads: i32) -> &mut Self {
+        self.compression_mt_dthreads = Some(threads);
+        self
+    }
+
+    /// Sets the size, in bytes, of the page cache used by [MigrateCompression::Xbzrle].
+    pub fn compression_xbzrle_cache(&mut self, size: u64) -> &mut Self {
+        self.compression_xbzrle_cache = Some(size);
+        self
+    }
+
+    /// Sets the initial percentage the domain's CPUs are throttled by with
+    /// [MigrateFlags::AUTO_CONVERGE].
+    pub fn auto_converge_initial(&mut self, percent: i32) -> &mut Self {
+        self.auto_converge_initial = Some(percent);
+        self
+    }
+
+    /// Sets the percentage the CPU throttling is increased by each time the migration is not
+    /// converging, with [MigrateFlags::AUTO_CONVERGE].
+    pub fn auto_converge_increment(&mut self, percent: i32) -> &mut Self {
+        self.auto_converge_increment = Some(percent);
+        self
+    }
+
+    /// Sets the number of connections used with [MigrateFlags::PARALLEL].
+    #[cfg(feature = "libvirt-5-2-0")]
+    pub fn parallel_connections(&mut self, connections: i32) -> &mut Self {
+        self.parallel_connections = Some(connections);
+        self
+    }
+
+    /// Sets the host name to expect in the TLS certificate of the destination host, with
+    /// [MigrateFlags::TLS]. Needed when it differs from the host name in the destination URI.
+    #[cfg(feature = "libvirt-6-0-0")]
+    pub fn tls_destination(&mut self, hostname: &str) -> Result<&mut Self, Error> {
+        self.tls_destination = Some(CString::new(hostname).map_err(Error::InvalidName)?);
+        Ok(self)
+    }
+
+    fn to_typed_params(&self) -> Result<TypedParams, VirtError> {
+        let mut params = TypedParams::new();
+        let strings = [
+            (virt_sys::VIR_MIGRATE_PARAM_URI as &[u8], &self.uri),
+            (virt_sys::VIR_MIGRATE_PARAM_DEST_NAME, &self.dest_name),
+            (virt_sys::VIR_MIGRATE_PARAM_DEST_XML, &self.dest_xml),
+            (virt_sys::VIR_MIGRATE_PARAM_PERSIST_XML, &self.persist_xml),
+            (virt_sys::VIR_MIGRATE_PARAM_GRAPHICS_URI, &self.graphics_uri),
+            (
+                virt_sys::VIR_MIGRATE_PARAM_LISTEN_ADDRESS,
+                &self.listen_address,
+            ),
+            #[cfg(feature = "libvirt-6-0-0")]
+            (
+                virt_sys::VIR_MIGRATE_PARAM_TLS_DESTINATION,
+                &self.tls_destination,
+            ),
+        ];
+        for (name, value) in strings.iter() {
+            if let Some(value) = value {
+                params.add_string(name, value)?;
+            }
+        }
+        for disk in &self.migrate_disks {
+            params.add_string(virt_sys::VIR_MIGRATE_PARAM_MIGRATE_DISKS, disk)?;
+        }
+        for method in &self.compression {
+            params.add_string(virt_sys::VIR_MIGRATE_PARAM_COMPRESSION, method.as_cstr())?;
+        }
+
+        let ullongs = [
+            (
+                virt_sys::VIR_MIGRATE_PARAM_BANDWIDTH as &[u8],
+                self.bandwidth,
+            ),
+            #[cfg(feature = "libvirt-5-1-0")]
+            (
+                virt_sys::VIR_MIGRATE_PARAM_BANDWIDTH_POSTCOPY,
+                self.bandwidth_postcopy,
+            ),
+            (
+                virt_sys::VIR_MIGRATE_PARAM_COMPRESSION_XBZRLE_CACHE,
+                self.compression_xbzrle_cache,
+            ),
+        ];
+        for (name, value) in ullongs.iter() {
+            if let Some(value) = value {
+                params.add_ullong(name, *value)?;
+            }
+        }
+
+        let ints = [
+            (
+                virt_sys::VIR_MIGRATE_PARAM_DISKS_PORT as &[u8],
+                self.disks_port,
+            ),
+            (
+                virt_sys::VIR_MIGRATE_PARAM_COMPRESSION_MT_LEVEL,
+                self.compression_mt_level,
+            ),
+            (
+                virt_sys::VIR_MIGRATE_PARAM_COMPRESSION_MT_THREADS,
+                self.compression_mt_threads,
+            ),
+            (
+                virt_sys::VIR_MIGRATE_PARAM_COMPRESSION_MT_DTHREADS,
+                self.compression_mt_dthreads,
+            ),
+            (
+                virt_sys::VIR_MIGRATE_PARAM_AUTO_CONVERGE_INITIAL,
+                self.auto_converge_initial,
+            ),
+            (
+                virt_sys::VIR_MIGRATE_PARAM_AUTO_CONVERGE_INCREMENT,
+                self.auto_converge_increment,
+            ),
+            #[cfg(feature = "libvirt-5-2-0")]
+            (
+                virt_sys::VIR_MIGRATE_PARAM_PARALLEL_CONNECTIONS,
+                self.parallel_connections,
+            ),
+        ];
+        for (name, value) in ints.iter() {
+            if let Some(value) = value {
+                params.add_int(name, *value)?;
+            }
+        }
+        Ok(params)
+    }
+}
+
+impl Domain {
+    /// Migrate the domain to the host `dest` is connected to. Returns the domain on the
+    /// destination host. This call blocks until the migration is complete, but the migration
+    /// can be monitored and tuned from other threads while it runs.
+    ///
+    /// With [MigrateFlags::PEER2PEER] the connection to the destination is made by the source
+    /// libvirt daemon, in which case [Domain::migrate_to_uri] is usually more convenient.
+    pub fn migrate(
+        &self,
+        dest: &Connection,
+        params: &MigrateParams,
+        flags: MigrateFlags,
+    ) -> Result<Domain, Error> {
+        let params = params.to_typed_params()?;
+        let ptr = cvt_null!(unsafe {
+            virt_sys::virDomainMigrate3(
+                self.0,
+                dest.as_ptr(),
+                params.as_ptr(),
+                params.len() as u32,
+                flags.bits(),
+            )
+        })?;
+        Ok(Domain(ptr))
+    }
+
+    /// Migrate the domain to another host, without the client having a connection to it.
+    /// This call blocks until the migration is complete, but the migration can be monitored
+    /// and tuned from other threads while it runs.
+    ///
+    /// With [MigrateFlags::PEER2PEER], `dest_uri` is the libvirt URI of the destination host
+    /// and the source libvirt daemon connects to it. Otherwise `dest_uri` must be `None`, and
+    /// the migration is performed directly by the hypervisor, to the URI given with
+    /// [MigrateParams::uri].
+    pub fn migrate_to_uri(
+        &self,
+        dest_uri: Option<&str>,
+        params: &MigrateParams,
+        flags: MigrateFlags,
+    ) -> Result<(), Error> {
+        let dest_uri_cstr = dest_uri
+            .map(CString::new)
+            .transpose()
+            .map_err(Error::InvalidUri)?;
+        let params = params.to_typed_params()?;
+        match unsafe {
+            virt_sys::virDomainMigrateToURI3(
+                self.0,
+                opt_ptr(&dest_uri_cstr),
+                params.as_ptr(),
+                params.len() as u32,
+                flags.bits(),
+            )
+        } {
+            -1 => Err(Error::from(VirtError::last_virt_error())),
+            _ => Ok(()),
+        }
+    }
+
+    /// Set the maximum tolerable time, in milliseconds, the domain may be paused at the end of
+    /// a live migration. Can be called while the migration is running.
+    pub fn migrate_set_max_downtime(&self, downtime_ms: u64) -> Result<(), VirtError> {
+        match unsafe { virt_sys::virDomainMigrateSetMaxDowntime(self.0, downtime_ms, 0) } {
+            -1 => Err(VirtError::last_virt_error()),
+            _ => Ok(()),
+        }
+    }
+
+    /// Returns the maximum tolerable time, in milliseconds, the domain may be paused at the end
+    /// of a live migration.
+    pub fn migrate_max_downtime(&self) -> Result<u64, VirtError> {
+        let mut downtime_ms = 0;
+        match unsafe { virt_sys::virDomainMigrateGetMaxDowntime(self.0, &mut downtime_ms, 0) } {
+            -1 => Err(VirtError::last_virt_error()),
+            _ => Ok(downtime_ms),
+        }
+    }
+
+    /// Set the maximum bandwidth, in MiB/s, a migration of the domain may use. Can be called
+    /// while the migration is running.
+    pub fn migrate_set_max_speed(
+        &self,
+        bandwidth: u64,
+        flags: MigrateMaxSpeedFlags,
+    ) -> Result<(), VirtError> {
+        match unsafe {
+            virt_sys::virDomainMigrateSetMaxSpeed(self.0, bandwidth as c_ulong, flags.bits())
+        } {
+            -1 => Err(VirtError::last_virt_error()),
+            _ => Ok(()),
+        }
+    }
+
+    /// Returns the maximum bandwidth, in MiB/s, a migration of the domain may use.
+    pub fn migrate_max_speed(&self, flags: MigrateMaxSpeedFlags) -> Result<u64, VirtError> {
+        let mut bandwidth: c_ulong = 0;
+        match unsafe { virt_sys::virDomainMigrateGetMaxSpeed(self.0, &mut bandwidth, flags.bits()) }
+        {
+            -1 => Err(VirtError::last_virt_error()),
+            _ => Ok(bandwidth as u64),
+        }
+    }
+
+    /// Set the size, in bytes, of the cache used for compressing repeatedly transferred memory
+    /// pages with [MigrateCompression::Xbzrle]. Can be called while the migration is running.
+    pub fn migrate_set_compression_cache(&self, size: u64) -> Result<(), VirtError> {
+        match unsafe { virt_sys::virDomainMigrateSetCompressionCache(self.0, size, 0) } {
+            -1 => Err(VirtError::last_virt_error()),
+            _ => Ok(()),
+        }
+    }
+
+    /// Returns the size, in bytes, of the cache used for compressing repeatedly transferred
+    /// memory pages with [MigrateCompression::Xbzrle].
+    pub fn migrate_compression_cache(&self) -> Result<u64, VirtError> {
+        let mut size = 0;
+        match unsafe { virt_sys::virDomainMigrateGetCompressionCache(self.0, &mut size, 0) } {
+            -1 => Err(VirtError::last_virt_error()),
+            _ => Ok(size),
+        }
+    }
+
+    /// Switch a running migration of the domain from pre-copy to post-copy mode. The domain is
+    /// resumed on the destination host and the remaining memory is transferred on demand, which
+    /// guarantees the migration converges. The migration must have been started with
+    /// [MigrateFlags::POSTCOPY].
+    ///
+    /// If the migration fails in post-copy mode, the domain can't be recovered on either host.
+    pub fn migrate_start_post_copy(&self) -> Result<(), VirtError> {
+        match unsafe { virt_sys::virDomainMigrateStartPostCopy(self.0, 0) } {
+            -1 => Err(VirtError::last_virt_error()),
+            _ => Ok(()),
+        }
+    }
+}
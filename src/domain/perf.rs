@@ -0,0 +1,175 @@
+use super::{Domain, ModificationImpact};
+use crate::{typed_params::TypedParams, VirtError};
+use std::ptr;
+
+/// The performance events monitored for a domain, using the perf subsystem of the host. The
+/// counters of enabled events are reported by the domain statistics. `None` values are not
+/// reported, or left unchanged when setting.
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq, Hash)]
+pub struct PerfEvents {
+    /// Cache usage, measured by Intel CMT.
+    pub cmt: Option<bool>,
+    /// Total system memory bandwidth, measured by Intel MBM.
+    pub mbmt: Option<bool>,
+    /// Local memory bandwidth, measured by Intel MBM.
+    pub mbml: Option<bool>,
+    /// Cache misses.
+    pub cache_misses: Option<bool>,
+    /// Cache references, i.e. cache accesses.
+    pub cache_references: Option<bool>,
+    /// Instructions executed.
+    pub instructions: Option<bool>,
+    /// CPU cycles.
+    pub cpu_cycles: Option<bool>,
+    /// Branch instructions executed.
+    pub branch_instructions: Option<bool>,
+    /// Mispredicted branches.
+    pub branch_misses: Option<bool>,
+    /// Bus cycles.
+    pub bus_cycles: Option<bool>,
+    /// Stalled CPU cycles in the frontend of the instruction pipeline.
+    pub stalled_cycles_frontend: Option<bool>,
+    /// Stalled CPU cycles in the backend of the instruction pipeline.
+    pub stalled_cycles_backend: Option<bool>,
+    /// CPU cycles, not affected by CPU frequency scaling.
+    pub ref_cpu_cycles: Option<bool>,
+    /// CPU clock time, a high resolution per-CPU timer.
+    pub cpu_clock: Option<bool>,
+    /// Task clock time, a clock count specific to the running task.
+    pub task_clock: Option<bool>,
+    /// Page faults.
+    pub page_faults: Option<bool>,
+    /// Context switches.
+    pub context_switches: Option<bool>,
+    /// Migrations of the process between host CPUs.
+    pub cpu_migrations: Option<bool>,
+    /// Minor page faults, handled without disk I/O.
+    pub page_faults_min: Option<bool>,
+    /// Major page faults, requiring disk I/O.
+    pub page_faults_maj: Option<bool>,
+    /// Alignment faults.
+    pub alignment_faults: Option<bool>,
+    /// Emulation faults, for instructions the kernel had to emulate.
+    pub emulation_faults: Option<bool>,
+}
+
+impl PerfEvents {
+    fn from_typed_params(params: &TypedParams) -> Result<Self, VirtError> {
+        let get = |name| params.get_boolean(name);
+        Ok(PerfEvents {
+            cmt: get(virt_sys::VIR_PERF_PARAM_CMT)?,
+            mbmt: get(virt_sys::VIR_PERF_PARAM_MBMT)?,
+            mbml: get(virt_sys::VIR_PERF_PARAM_MBML)?,
+            cache_misses: get(virt_sys::VIR_PERF_PARAM_CACHE_MISSES)?,
+            cache_references: get(virt_sys::VIR_PERF_PARAM_CACHE_REFERENCES)?,
+            instructions: get(virt_sys::VIR_PERF_PARAM_INSTRUCTIONS)?,
+            cpu_cycles: get(virt_sys::VIR_PERF_PARAM_CPU_CYCLES)?,
+            branch_instructions: get(virt_sys::VIR_PERF_PARAM_BRANCH_INSTRUCTIONS)?,
+            branch_misses: get(virt_sys::VIR_PERF_PARAM_BRANCH_MISSES)?,
+            bus_cycles: get(virt_sys::VIR_PERF_PARAM_BUS_CYCLES)?,
+            stalled_cycles_frontend: get(virt_sys::VIR_PERF_PARAM_STALLED_CYCLES_FRONTEND)?,
+            stalled_cycles_backend: get(virt_sys::VIR_PERF_PARAM_STALLED_CYCLES_BACKEND)?,
+            ref_cpu_cycles: get(virt_sys::VIR_PERF_PARAM_REF_CPU_CYCLES)?,
+            cpu_clock: get(virt_sys::VIR_PERF_PARAM_CPU_CLOCK)?,
+            task_clock: get(virt_sys::VIR_PERF_PARAM_TASK_CLOCK)?,
+            page_faults: get(virt_sys::VIR_PERF_PARAM_PAGE_FAULTS)?,
+            context_switches: get(virt_sys::VIR_PERF_PARAM_CONTEXT_SWITCHES)?,
+            cpu_migrations: get(virt_sys::VIR_PERF_PARAM_CPU_MIGRATIONS)?,
+            page_faults_min: get(virt_sys::VIR_PERF_PARAM_PAGE_FAULTS_MIN)?,
+            page_faults_maj: get(virt_sys::VIR_PERF_PARAM_PAGE_FAULTS_MAJ)?,
+            alignment_faults: get(virt_sys::VIR_PERF_PARAM_ALIGNMENT_FAULTS)?,
+            emulation_faults: get(virt_sys::VIR_PERF_PARAM_EMULATION_FAULTS)?,
+        })
+    }
+
+    fn to_typed_params(self) -> Result<TypedParams, VirtError> {
+        let mut params = TypedParams::new();
+        let values = [
+            (virt_sys::VIR_PERF_PARAM_CMT as &[u8], self.cmt),
+            (virt_sys::VIR_PERF_PARAM_MBMT, self.mbmt),
+            (virt_sys::VIR_PERF_PARAM_MBML, self.mbml),
+            (virt_sys::VIR_PERF_PARAM_CACHE_MISSES, self.cache_misses),
+            (
+                virt_sys::VIR_PERF_PARAM_CACHE_REFERENCES,
+                self.cache_references,
+            ),
+            (virt_sys::VIR_PERF_PARAM_INSTRUCTIONS, self.instructions),
+            (virt_sys::VIR_PERF_PARAM_CPU_CYCLES, self.cpu_cycles),
+            (
+                virt_sys::VIR_PERF_PARAM_BRANCH_INSTRUCTIONS,
+                self.branch_instructions,
+            ),
+            (virt_sys::VIR_PERF_PARAM_BRANCH_MISSES, self.branch_misses),
+            (virt_sys::VIR_PERF_PARAM_BUS_CYCLES, self.bus_cycles),
+            (
+                virt_sys::VIR_PERF_PARAM_STALLED_CYCLES_FRONTEND,
+                self.stalled_cycles_frontend,
+            ),
+            (
+                virt_sys::VIR_PERF_PARAM_STALLED_CYCLES_BACKEND,
+                self.stalled_cycles_backend,
+            ),
+            (virt_sys::VIR_PERF_PARAM_REF_CPU_CYCLES, self.ref_cpu_cycles),
+            (virt_sys::VIR_PERF_PARAM_CPU_CLOCK, self.cpu_clock),
+            (virt_sys::VIR_PERF_PARAM_TASK_CLOCK, self.task_clock),
+            (virt_sys::VIR_PERF_PARAM_PAGE_FAULTS, self.page_faults),
+            (
+                virt_sys::VIR_PERF_PARAM_CONTEXT_SWITCHES,
+                self.context_switches,
+            ),
+            (virt_sys::VIR_PERF_PARAM_CPU_MIGRATIONS, self.cpu_migrations),
+            (
+                virt_sys::VIR_PERF_PARAM_PAGE_FAULTS_MIN,
+                self.page_faults_min,
+            ),
+            (
+                virt_sys::VIR_PERF_PARAM_PAGE_FAULTS_MAJ,
+                self.page_faults_maj,
+            ),
+            (
+                virt_sys::VIR_PERF_PARAM_ALIGNMENT_FAULTS,
+                self.alignment_faults,
+            ),
+            (
+                virt_sys::VIR_PERF_PARAM_EMULATION_FAULTS,
+                self.emulation_faults,
+            ),
+        ];
+        for (name, value) in values.iter() {
+            if let Some(value) = value {
+                params.add_boolean(name, *value)?;
+            }
+        }
+        Ok(params)
+    }
+}
+
+impl Domain {
+    /// Returns which performance events are monitored for the domain.
+    pub fn perf_events(&self, flags: ModificationImpact) -> Result<PerfEvents, VirtError> {
+        let mut params = ptr::null_mut();
+        let mut nparams = 0;
+        match unsafe {
+            virt_sys::virDomainGetPerfEvents(self.0, &mut params, &mut nparams, flags.bits())
+        } {
+            -1 => Err(VirtError::last_virt_error()),
+            _ => PerfEvents::from_typed_params(&unsafe { TypedParams::from_raw(params, nparams) }),
+        }
+    }
+
+    /// Enable and disable monitoring of performance events for the domain. Only the events
+    /// that are `Some` are changed.
+    pub fn set_perf_events(
+        &self,
+        events: &PerfEvents,
+        flags: ModificationImpact,
+    ) -> Result<(), VirtError> {
+        let params = events.to_typed_params()?;
+        match unsafe {
+            virt_sys::virDomainSetPerfEvents(self.0, params.as_ptr(), params.len(), flags.bits())
+        } {
+            -1 => Err(VirtError::last_virt_error()),
+            _ => Ok(()),
+        }
+    }
+}
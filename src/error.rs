@@ -9,7 +9,16 @@ pub enum Error {
     InvalidUri(std::ffi::NulError),
     InvalidXml(std::ffi::NulError),
     InvalidName(std::ffi::NulError),
+    InvalidPath(std::ffi::NulError),
+    InvalidCpuList(std::ffi::NulError),
+    InvalidIndex(std::num::TryFromIntError),
     Utf8Error(std::str::Utf8Error),
+    Io(std::io::Error),
+    InvalidAddress(std::net::AddrParseError),
+    /// A password contained a nul byte. The password itself is not kept.
+    InvalidPassword,
+    /// A real-time signal number was out of range.
+    InvalidSignal,
 }
 
 impl From<VirtError> for Error {
@@ -26,7 +35,14 @@ impl fmt::Display for Error {
             InvalidUri(_) => "Invalid URI".fmt(f),
             InvalidXml(_) => "Invalid XML".fmt(f),
             InvalidName(_) => "Invalid name".fmt(f),
+            InvalidPath(_) => "Invalid path".fmt(f),
+            InvalidCpuList(_) => "Invalid CPU list".fmt(f),
+            InvalidIndex(_) => "Index out of range".fmt(f),
             Utf8Error(_) => "String is not valid UTF-8".fmt(f),
+            Io(_) => "I/O error".fmt(f),
+            InvalidAddress(_) => "Invalid IP address".fmt(f),
+            InvalidPassword => "Invalid password".fmt(f),
+            InvalidSignal => "Invalid signal".fmt(f),
         }
     }
 }
@@ -39,7 +55,13 @@ impl std::error::Error for Error {
             InvalidUri(e) => Some(e),
             InvalidXml(e) => Some(e),
             InvalidName(e) => Some(e),
+            InvalidPath(e) => Some(e),
+            InvalidCpuList(e) => Some(e),
+            InvalidIndex(e) => Some(e),
             Utf8Error(e) => Some(e),
+            Io(e) => Some(e),
+            InvalidAddress(e) => Some(e),
+            InvalidPassword | InvalidSignal => None,
         }
     }
 }
@@ -75,6 +97,11 @@ impl VirtError {
         unsafe { &*self.0 }
     }
 
+    /// Returns true if this error has the given libvirt error code.
+    pub(crate) fn is_code(&self, code: virt_sys::virErrorNumber) -> bool {
+        self.as_ref().code == code as i32
+    }
+
     /// Returns the human-readable informative error message given by libvirt.
     pub fn message(&self) -> Cow<str> {
         unsafe { CStr::from_ptr(self.as_ref().message) }.to_string_lossy()
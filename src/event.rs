@@ -0,0 +1,20 @@
+use crate::VirtError;
+
+/// Registers the default event loop implementation built into libvirt. This must be done before
+/// opening any connection that should deliver events, such as stream events. The event loop is
+/// then driven by calling [run_default_impl] repeatedly, usually from a dedicated thread.
+pub fn register_default_impl() -> Result<(), VirtError> {
+    match unsafe { virt_sys::virEventRegisterDefaultImpl() } {
+        -1 => Err(VirtError::last_virt_error()),
+        _ => Ok(()),
+    }
+}
+
+/// Runs one iteration of the default event loop registered with [register_default_impl]. Blocks
+/// until at least one event has been dispatched, or a timeout has expired.
+pub fn run_default_impl() -> Result<(), VirtError> {
+    match unsafe { virt_sys::virEventRunDefaultImpl() } {
+        -1 => Err(VirtError::last_virt_error()),
+        _ => Ok(()),
+    }
+}
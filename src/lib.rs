@@ -4,6 +4,12 @@ pub use virt_sys as sys;
 #[macro_use]
 mod macros;
 
+/// Checkpoints and incremental backups of domains.
+#[cfg(feature = "libvirt-6-0-0")]
+pub mod checkpoint;
+#[cfg(feature = "libvirt-6-0-0")]
+pub use checkpoint::DomainCheckpoint;
+
 /// Types related to connecting to a hypervisor.
 pub mod connection;
 pub use connection::Connection;
@@ -14,7 +20,33 @@ pub use domain::Domain;
 mod error;
 pub use error::{Error, VirtError};
 
+/// Event loop integration, needed for receiving events from libvirt.
+pub mod event;
+
+/// Virtual networks managed by libvirt.
+pub mod network;
+pub use network::Network;
+
+/// Security labels of domains and security features of hosts.
+pub mod security;
+
+/// Snapshots of the state of domains.
+pub mod snapshot;
+pub use snapshot::DomainSnapshot;
+
+/// Storage pools managed by libvirt.
+pub mod storage_pool;
+pub use storage_pool::StoragePool;
+
+/// Data streams between the client and the hypervisor.
+pub mod stream;
+pub use stream::Stream;
+
 pub mod version;
 
+mod typed_params;
+
+mod util;
+
 mod wrapper;
 pub use wrapper::Wrapper;
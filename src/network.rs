@@ -0,0 +1,247 @@
+use crate::{
+    util::{copy_string, take_string, take_wrapper_array},
+    Connection, Error, VirtError, Wrapper,
+};
+use std::{
+    ffi::CString,
+    mem,
+    os::raw::{c_int, c_uchar},
+    ptr,
+};
+
+mod dhcp;
+pub use dhcp::DhcpLease;
+
+#[cfg(feature = "libvirt-5-5-0")]
+mod port;
+#[cfg(feature = "libvirt-5-5-0")]
+pub use port::{NetworkPort, NetworkPortCreateFlags};
+
+mod update;
+pub use update::{NetworkUpdateCommand, NetworkUpdateFlags, NetworkUpdateSection};
+
+bitflags::bitflags! {
+    /// Flags affecting [Network::xml_desc].
+    pub struct NetworkXmlFlags: u32 {
+        /// Return the persistent configuration of the network, instead of the running one.
+        const INACTIVE = virt_sys::VIR_NETWORK_XML_INACTIVE;
+    }
+}
+
+bitflags::bitflags! {
+    /// Flags filtering the networks returned by [Connection::list_all_networks]. Within each
+    /// pair of related flags, no flags means no filtering on that property.
+    pub struct ListAllNetworksFlags: u32 {
+        /// Only list networks that are not running.
+        const INACTIVE = virt_sys::VIR_CONNECT_LIST_NETWORKS_INACTIVE;
+        /// Only list running networks.
+        const ACTIVE = virt_sys::VIR_CONNECT_LIST_NETWORKS_ACTIVE;
+        /// Only list networks with a persistent configuration.
+        const PERSISTENT = virt_sys::VIR_CONNECT_LIST_NETWORKS_PERSISTENT;
+        /// Only list transient networks.
+        const TRANSIENT = virt_sys::VIR_CONNECT_LIST_NETWORKS_TRANSIENT;
+        /// Only list networks that are started automatically.
+        const AUTOSTART = virt_sys::VIR_CONNECT_LIST_NETWORKS_AUTOSTART;
+        /// Only list networks that are not started automatically.
+        const NO_AUTOSTART = virt_sys::VIR_CONNECT_LIST_NETWORKS_NO_AUTOSTART;
+    }
+}
+
+/// A virtual network managed by libvirt, that domains can be connected to.
+pub struct Network(virt_sys::virNetworkPtr);
+
+// Safety: libvirt is thread safe since 0.6.0. It can handle multiple threads making calls to the
+// same virNetwork instance.
+unsafe impl Send for Network {}
+unsafe impl Sync for Network {}
+
+impl Network {
+    /// See [Connection::create_network].
+    pub(crate) fn create_from_xml(connection: &Connection, xml: &str) -> Result<Self, Error> {
+        let xml_cstr = CString::new(xml).map_err(Error::InvalidXml)?;
+        let ptr = cvt_null!(unsafe {
+            virt_sys::virNetworkCreateXML(connection.as_ptr(), xml_cstr.as_ptr())
+        })?;
+        Ok(Network(ptr))
+    }
+
+    /// See [Connection::define_network].
+    pub(crate) fn define_from_xml(connection: &Connection, xml: &str) -> Result<Self, Error> {
+        let xml_cstr = CString::new(xml).map_err(Error::InvalidXml)?;
+        let ptr = cvt_null!(unsafe {
+            virt_sys::virNetworkDefineXML(connection.as_ptr(), xml_cstr.as_ptr())
+        })?;
+        Ok(Network(ptr))
+    }
+
+    /// See [Connection::network_by_name].
+    pub(crate) fn lookup_by_name(connection: &Connection, name: &str) -> Result<Self, Error> {
+        let name_cstr = CString::new(name).map_err(Error::InvalidName)?;
+        let ptr = cvt_null!(unsafe {
+            virt_sys::virNetworkLookupByName(connection.as_ptr(), name_cstr.as_ptr())
+        })?;
+        Ok(Network(ptr))
+    }
+
+    /// See [Connection::network_by_uuid].
+    pub(crate) fn lookup_by_uuid(
+        connection: &Connection,
+        uuid: &[u8; 16],
+    ) -> Result<Self, VirtError> {
+        let ptr = cvt_null!(unsafe {
+            virt_sys::virNetworkLookupByUUID(connection.as_ptr(), uuid.as_ptr())
+        })?;
+        Ok(Network(ptr))
+    }
+
+    /// See [Connection::list_all_networks].
+    pub(crate) fn list_all(
+        connection: &Connection,
+        flags: ListAllNetworksFlags,
+    ) -> Result<Vec<Self>, VirtError> {
+        let mut networks = ptr::null_mut();
+        match unsafe {
+            virt_sys::virConnectListAllNetworks(connection.as_ptr(), &mut networks, flags.bits())
+        } {
+            -1 => Err(VirtError::last_virt_error()),
+            n => Ok(unsafe { take_wrapper_array(networks, n as usize) }),
+        }
+    }
+
+    /// Start this defined, but inactive, network.
+    pub fn create(&self) -> Result<(), VirtError> {
+        match unsafe { virt_sys::virNetworkCreate(self.0) } {
+            -1 => Err(VirtError::last_virt_error()),
+            _ => Ok(()),
+        }
+    }
+
+    /// Stop the network. A transient network disappears, a persistent one remains defined but
+    /// inactive. Domains connected to the network lose connectivity.
+    pub fn destroy(&self) -> Result<(), VirtError> {
+        match unsafe { virt_sys::virNetworkDestroy(self.0) } {
+            -1 => Err(VirtError::last_virt_error()),
+            _ => Ok(()),
+        }
+    }
+
+    /// Remove the persistent configuration of the network. A running network keeps running
+    /// as a transient network.
+    pub fn undefine(&self) -> Result<(), VirtError> {
+        match unsafe { virt_sys::virNetworkUndefine(self.0) } {
+            -1 => Err(VirtError::last_virt_error()),
+            _ => Ok(()),
+        }
+    }
+
+    /// Returns the name of the network.
+    pub fn name(&self) -> Result<String, Error> {
+        let name_ptr = cvt_null!(unsafe { virt_sys::virNetworkGetName(self.0) })?;
+        unsafe { copy_string(name_ptr) }
+    }
+
+    /// Returns the UUID of the network, in binary form.
+    pub fn uuid(&self) -> Result<[u8; 16], VirtError> {
+        let mut uuid = [0; virt_sys::VIR_UUID_BUFLEN as usize];
+        match unsafe { virt_sys::virNetworkGetUUID(self.0, uuid.as_mut_ptr() as *mut c_uchar) } {
+            -1 => Err(VirtError::last_virt_error()),
+            _ => Ok(uuid),
+        }
+    }
+
+    /// Returns the XML description of the network.
+    pub fn xml_desc(&self, flags: NetworkXmlFlags) -> Result<String, Error> {
+        let xml_ptr = cvt_null!(unsafe { virt_sys::virNetworkGetXMLDesc(self.0, flags.bits()) })?;
+        unsafe { take_string(xml_ptr) }
+    }
+
+    /// Returns the name of the bridge device on the host the network is attached to, such as
+    /// `virbr0`.
+    pub fn bridge_name(&self) -> Result<String, Error> {
+        let name_ptr = cvt_null!(unsafe { virt_sys::virNetworkGetBridgeName(self.0) })?;
+        unsafe { take_string(name_ptr) }
+    }
+
+    /// Returns whether the network is started automatically when the host boots.
+    pub fn autostart(&self) -> Result<bool, VirtError> {
+        let mut autostart = 0;
+        match unsafe { virt_sys::virNetworkGetAutostart(self.0, &mut autostart) } {
+            -1 => Err(VirtError::last_virt_error()),
+            _ => Ok(autostart != 0),
+        }
+    }
+
+    /// Set whether the network is started automatically when the host boots.
+    pub fn set_autostart(&self, autostart: bool) -> Result<(), VirtError> {
+        match unsafe { virt_sys::virNetworkSetAutostart(self.0, autostart as c_int) } {
+            -1 => Err(VirtError::last_virt_error()),
+            _ => Ok(()),
+        }
+    }
+
+    /// Returns whether the network is running.
+    pub fn is_active(&self) -> Result<bool, VirtError> {
+        match unsafe { virt_sys::virNetworkIsActive(self.0) } {
+            -1 => Err(VirtError::last_virt_error()),
+            0 => Ok(false),
+            _ => Ok(true),
+        }
+    }
+
+    /// Returns whether the network has a persistent configuration, as opposed to being
+    /// transient.
+    pub fn is_persistent(&self) -> Result<bool, VirtError> {
+        match unsafe { virt_sys::virNetworkIsPersistent(self.0) } {
+            -1 => Err(VirtError::last_virt_error()),
+            0 => Ok(false),
+            _ => Ok(true),
+        }
+    }
+
+    /// Free the network object. The network itself is not affected.
+    /// If this is not explicitly called it will be called by the `Drop` implementation. And any
+    /// error will be logged to the error level.
+    ///
+    /// The only reason to call this explicitly is if you want to handle the error in some other
+    /// way than just logging it.
+    pub fn free(self) -> Result<(), VirtError> {
+        let result = self.free_internal();
+        mem::forget(self);
+        result
+    }
+
+    fn free_internal(&self) -> Result<(), VirtError> {
+        match unsafe { virt_sys::virNetworkFree(self.0) } {
+            -1 => Err(VirtError::last_virt_error()),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl crate::Wrapper for Network {
+    type Ptr = virt_sys::virNetworkPtr;
+
+    unsafe fn from_ptr(ptr: Self::Ptr) -> Self {
+        Self(ptr)
+    }
+
+    fn as_ptr(&self) -> Self::Ptr {
+        self.0
+    }
+}
+
+impl Clone for Network {
+    fn clone(&self) -> Self {
+        let ret = unsafe { virt_sys::virNetworkRef(self.0) };
+        assert_eq!(ret, 0, "Unexpected error from virNetworkRef");
+        Self(self.0)
+    }
+}
+
+impl Drop for Network {
+    fn drop(&mut self) {
+        if let Err(e) = self.free_internal() {
+            log::error!("Error when freeing network: {}", e);
+        }
+    }
+}
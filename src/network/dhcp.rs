@@ -0,0 +1,91 @@
+use super::Network;
+use crate::{
+    util::{copy_opt_string, copy_string, opt_ptr},
+    Error, VirtError,
+};
+use std::{
+    ffi::CString,
+    net::IpAddr,
+    ptr, slice,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+/// A lease handed out by the DHCP server of a network. Returned by [Network::dhcp_leases].
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct DhcpLease {
+    /// The name of the network interface on the host the lease was handed out on, such as
+    /// `virbr0`.
+    pub iface: String,
+    /// When the lease expires. Leases that never expire have an expiry time of
+    /// [UNIX_EPOCH].
+    pub expiry: SystemTime,
+    /// The leased address.
+    pub ip: IpAddr,
+    /// The prefix length of the network the address is in.
+    pub prefix: u8,
+    /// The MAC address of the client.
+    pub mac: Option<String>,
+    /// The identity association identifier of the client. Only used for DHCPv6.
+    pub iaid: Option<String>,
+    /// The hostname reported by the client.
+    pub hostname: Option<String>,
+    /// The client identifier, or the DUID for DHCPv6.
+    pub clientid: Option<String>,
+}
+
+impl DhcpLease {
+    /// # Safety
+    ///
+    /// `lease` must point to a valid `virNetworkDHCPLease`.
+    unsafe fn from_raw(lease: &virt_sys::virNetworkDHCPLease) -> Result<Self, Error> {
+        Ok(DhcpLease {
+            iface: copy_string(lease.iface)?,
+            expiry: UNIX_EPOCH + Duration::from_secs(lease.expirytime.max(0) as u64),
+            ip: copy_string(lease.ipaddr)?
+                .parse()
+                .map_err(Error::InvalidAddress)?,
+            prefix: lease.prefix as u8,
+            mac: copy_opt_string(lease.mac)?,
+            iaid: copy_opt_string(lease.iaid)?,
+            hostname: copy_opt_string(lease.hostname)?,
+            clientid: copy_opt_string(lease.clientid)?,
+        })
+    }
+}
+
+impl Network {
+    /// Returns the DHCP leases handed out by the network, or only the ones for the client
+    /// with MAC address `mac`.
+    pub fn dhcp_leases(&self, mac: Option<&str>) -> Result<Vec<DhcpLease>, Error> {
+        let mac = mac
+            .map(CString::new)
+            .transpose()
+            .map_err(Error::InvalidName)?;
+        let mut leases = ptr::null_mut();
+        let n = match unsafe {
+            virt_sys::virNetworkGetDHCPLeases(self.0, opt_ptr(&mac), &mut leases, 0)
+        } {
+            -1 => return Err(Error::from(VirtError::last_virt_error())),
+            n => n as usize,
+        };
+        let lease_ptrs = if leases.is_null() {
+            &[][..]
+        } else {
+            unsafe { slice::from_raw_parts(leases, n) }
+        };
+        let result = lease_ptrs
+            .iter()
+            .map(|&lease| unsafe { DhcpLease::from_raw(&*lease) })
+            .collect();
+        unsafe {
+            for &lease in lease_ptrs {
+                virt_sys::virNetworkDHCPLeaseFree(lease);
+            }
+            // libvirt may hand back an array even when there are no leases.
+            if !leases.is_null() {
+                libc::free(leases as *mut _);
+            }
+        }
+        result
+    }
+}
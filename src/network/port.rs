@@ -0,0 +1,156 @@
+use super::Network;
+use crate::{
+    domain::InterfaceBandwidth,
+    typed_params::TypedParams,
+    util::{take_string, take_wrapper_array},
+    Error, VirtError,
+};
+use std::{ffi::CString, mem, os::raw::c_uchar, ptr};
+
+bitflags::bitflags! {
+    /// Flags affecting [Network::create_port].
+    pub struct NetworkPortCreateFlags: u32 {
+        /// Reclaim the resources of a port that already exists, such as one that was in use
+        /// before libvirt was restarted, instead of allocating new ones.
+        const RECLAIM = virt_sys::VIR_NETWORK_PORT_CREATE_RECLAIM;
+    }
+}
+
+/// A port on a [Network], connecting a network interface that is not managed by libvirt to
+/// the network.
+pub struct NetworkPort(virt_sys::virNetworkPortPtr);
+
+// Safety: libvirt is thread safe since 0.6.0. It can handle multiple threads making calls to the
+// same virNetworkPort instance.
+unsafe impl Send for NetworkPort {}
+unsafe impl Sync for NetworkPort {}
+
+impl NetworkPort {
+    /// Returns the XML description of the port.
+    pub fn xml_desc(&self) -> Result<String, Error> {
+        let xml_ptr = cvt_null!(unsafe { virt_sys::virNetworkPortGetXMLDesc(self.0, 0) })?;
+        unsafe { take_string(xml_ptr) }
+    }
+
+    /// Returns the UUID of the port, in binary form.
+    pub fn uuid(&self) -> Result<[u8; 16], VirtError> {
+        let mut uuid = [0; virt_sys::VIR_UUID_BUFLEN as usize];
+        match unsafe { virt_sys::virNetworkPortGetUUID(self.0, uuid.as_mut_ptr() as *mut c_uchar) }
+        {
+            -1 => Err(VirtError::last_virt_error()),
+            _ => Ok(uuid),
+        }
+    }
+
+    /// Returns the bandwidth limits of the port.
+    pub fn parameters(&self) -> Result<InterfaceBandwidth, VirtError> {
+        let mut params = ptr::null_mut();
+        let mut nparams = 0;
+        match unsafe { virt_sys::virNetworkPortGetParameters(self.0, &mut params, &mut nparams, 0) }
+        {
+            -1 => Err(VirtError::last_virt_error()),
+            // The port parameters have the same names as the ones of domain interfaces.
+            _ => InterfaceBandwidth::from_typed_params(&unsafe {
+                TypedParams::from_raw(params, nparams)
+            }),
+        }
+    }
+
+    /// Change the bandwidth limits of the port. Only the limits that are `Some` are changed.
+    pub fn set_parameters(&self, bandwidth: &InterfaceBandwidth) -> Result<(), Error> {
+        let params = bandwidth.to_typed_params()?;
+        match unsafe {
+            virt_sys::virNetworkPortSetParameters(self.0, params.as_ptr(), params.len(), 0)
+        } {
+            -1 => Err(Error::from(VirtError::last_virt_error())),
+            _ => Ok(()),
+        }
+    }
+
+    /// Delete the port, releasing the resources allocated to it. The port object should not be
+    /// used for anything but freeing after this.
+    pub fn delete(&self) -> Result<(), VirtError> {
+        match unsafe { virt_sys::virNetworkPortDelete(self.0, 0) } {
+            -1 => Err(VirtError::last_virt_error()),
+            _ => Ok(()),
+        }
+    }
+
+    /// Free the port object. The port itself is kept.
+    /// If this is not explicitly called it will be called by the `Drop` implementation. And any
+    /// error will be logged to the error level.
+    ///
+    /// The only reason to call this explicitly is if you want to handle the error in some other
+    /// way than just logging it.
+    pub fn free(self) -> Result<(), VirtError> {
+        let result = self.free_internal();
+        mem::forget(self);
+        result
+    }
+
+    fn free_internal(&self) -> Result<(), VirtError> {
+        match unsafe { virt_sys::virNetworkPortFree(self.0) } {
+            -1 => Err(VirtError::last_virt_error()),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl Network {
+    /// Create a port on the network, as described by the port XML.
+    pub fn create_port(
+        &self,
+        xml: &str,
+        flags: NetworkPortCreateFlags,
+    ) -> Result<NetworkPort, Error> {
+        let xml_cstr = CString::new(xml).map_err(Error::InvalidXml)?;
+        let ptr = cvt_null!(unsafe {
+            virt_sys::virNetworkPortCreateXML(self.0, xml_cstr.as_ptr(), flags.bits())
+        })?;
+        Ok(NetworkPort(ptr))
+    }
+
+    /// Look up a port of the network by its UUID, in binary form.
+    pub fn port_by_uuid(&self, uuid: &[u8; 16]) -> Result<NetworkPort, VirtError> {
+        let ptr =
+            cvt_null!(unsafe { virt_sys::virNetworkPortLookupByUUID(self.0, uuid.as_ptr()) })?;
+        Ok(NetworkPort(ptr))
+    }
+
+    /// Returns all ports of the network.
+    pub fn list_all_ports(&self) -> Result<Vec<NetworkPort>, VirtError> {
+        let mut ports = ptr::null_mut();
+        match unsafe { virt_sys::virNetworkListAllPorts(self.0, &mut ports, 0) } {
+            -1 => Err(VirtError::last_virt_error()),
+            n => Ok(unsafe { take_wrapper_array(ports, n as usize) }),
+        }
+    }
+}
+
+impl crate::Wrapper for NetworkPort {
+    type Ptr = virt_sys::virNetworkPortPtr;
+
+    unsafe fn from_ptr(ptr: Self::Ptr) -> Self {
+        Self(ptr)
+    }
+
+    fn as_ptr(&self) -> Self::Ptr {
+        self.0
+    }
+}
+
+impl Clone for NetworkPort {
+    fn clone(&self) -> Self {
+        let ret = unsafe { virt_sys::virNetworkPortRef(self.0) };
+        assert_eq!(ret, 0, "Unexpected error from virNetworkPortRef");
+        Self(self.0)
+    }
+}
+
+impl Drop for NetworkPort {
+    fn drop(&mut self) {
+        if let Err(e) = self.free_internal() {
+            log::error!("Error when freeing network port: {}", e);
+        }
+    }
+}
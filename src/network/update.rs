@@ -0,0 +1,129 @@
+use super::Network;
+use crate::{Error, VirtError};
+use std::{convert::TryFrom, ffi::CString, os::raw::c_int};
+
+bitflags::bitflags! {
+    /// Selects whether [Network::update] changes the running network, its persistent
+    /// configuration, or both.
+    pub struct NetworkUpdateFlags: u32 {
+        /// Affect the running network if it is active, and its configuration otherwise.
+        const AFFECT_CURRENT = virt_sys::VIR_NETWORK_UPDATE_AFFECT_CURRENT;
+        /// Affect the running network.
+        const AFFECT_LIVE = virt_sys::VIR_NETWORK_UPDATE_AFFECT_LIVE;
+        /// Affect the persistent configuration of the network.
+        const AFFECT_CONFIG = virt_sys::VIR_NETWORK_UPDATE_AFFECT_CONFIG;
+    }
+}
+
+/// How [Network::update] changes a section of the network configuration.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum NetworkUpdateCommand {
+    /// Replace an existing element, matched by its identifying attributes.
+    Modify,
+    /// Remove an existing element, matched by its identifying attributes.
+    Delete,
+    /// Add a new element after the existing ones.
+    AddLast,
+    /// Add a new element before the existing ones.
+    AddFirst,
+}
+
+impl NetworkUpdateCommand {
+    fn to_raw(self) -> virt_sys::virNetworkUpdateCommand {
+        match self {
+            NetworkUpdateCommand::Modify => virt_sys::VIR_NETWORK_UPDATE_COMMAND_MODIFY,
+            NetworkUpdateCommand::Delete => virt_sys::VIR_NETWORK_UPDATE_COMMAND_DELETE,
+            NetworkUpdateCommand::AddLast => virt_sys::VIR_NETWORK_UPDATE_COMMAND_ADD_LAST,
+            NetworkUpdateCommand::AddFirst => virt_sys::VIR_NETWORK_UPDATE_COMMAND_ADD_FIRST,
+        }
+    }
+}
+
+/// The section of the network configuration changed by [Network::update]. Each variant names
+/// the XML element the update applies to.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum NetworkUpdateSection {
+    /// `<bridge>`
+    Bridge,
+    /// `<domain>`
+    Domain,
+    /// `<ip>`
+    Ip,
+    /// `<ip>/<dhcp>/<host>`, a static DHCP reservation.
+    IpDhcpHost,
+    /// `<ip>/<dhcp>/<range>`
+    IpDhcpRange,
+    /// `<forward>`
+    Forward,
+    /// `<forward>/<interface>`
+    ForwardInterface,
+    /// `<forward>/<pf>`
+    ForwardPf,
+    /// `<portgroup>`
+    Portgroup,
+    /// `<dns>/<host>`
+    DnsHost,
+    /// `<dns>/<txt>`
+    DnsTxt,
+    /// `<dns>/<srv>`
+    DnsSrv,
+}
+
+impl NetworkUpdateSection {
+    fn to_raw(self) -> virt_sys::virNetworkUpdateSection {
+        match self {
+            NetworkUpdateSection::Bridge => virt_sys::VIR_NETWORK_SECTION_BRIDGE,
+            NetworkUpdateSection::Domain => virt_sys::VIR_NETWORK_SECTION_DOMAIN,
+            NetworkUpdateSection::Ip => virt_sys::VIR_NETWORK_SECTION_IP,
+            NetworkUpdateSection::IpDhcpHost => virt_sys::VIR_NETWORK_SECTION_IP_DHCP_HOST,
+            NetworkUpdateSection::IpDhcpRange => virt_sys::VIR_NETWORK_SECTION_IP_DHCP_RANGE,
+            NetworkUpdateSection::Forward => virt_sys::VIR_NETWORK_SECTION_FORWARD,
+            NetworkUpdateSection::ForwardInterface => {
+                virt_sys::VIR_NETWORK_SECTION_FORWARD_INTERFACE
+            }
+            NetworkUpdateSection::ForwardPf => virt_sys::VIR_NETWORK_SECTION_FORWARD_PF,
+            NetworkUpdateSection::Portgroup => virt_sys::VIR_NETWORK_SECTION_PORTGROUP,
+            NetworkUpdateSection::DnsHost => virt_sys::VIR_NETWORK_SECTION_DNS_HOST,
+            NetworkUpdateSection::DnsTxt => virt_sys::VIR_NETWORK_SECTION_DNS_TXT,
+            NetworkUpdateSection::DnsSrv => virt_sys::VIR_NETWORK_SECTION_DNS_SRV,
+        }
+    }
+}
+
+impl Network {
+    /// Change a single section of the network configuration, without restarting the network.
+    ///
+    /// `xml` is the complete XML element to add, modify or delete, such as
+    /// `<host mac='52:54:00:00:00:01' ip='192.168.122.10'/>` for
+    /// [NetworkUpdateSection::IpDhcpHost]. For sections inside an `<ip>` element,
+    /// `parent_index` selects which `<ip>` element is changed. If it is `None`, the first one
+    /// with a matching address family is used. An index that does not fit in a C `int` is
+    /// rejected with [Error::InvalidIndex].
+    pub fn update(
+        &self,
+        command: NetworkUpdateCommand,
+        section: NetworkUpdateSection,
+        parent_index: Option<u32>,
+        xml: &str,
+        flags: NetworkUpdateFlags,
+    ) -> Result<(), Error> {
+        let xml_cstr = CString::new(xml).map_err(Error::InvalidXml)?;
+        let parent_index = match parent_index {
+            Some(index) => c_int::try_from(index).map_err(Error::InvalidIndex)?,
+            None => -1,
+        };
+        match unsafe {
+            virt_sys::virNetworkUpdate(
+                self.0,
+                command.to_raw(),
+                section.to_raw(),
+                parent_index,
+                xml_cstr.as_ptr(),
+                flags.bits(),
+            )
+        } {
+            -1 => Err(Error::from(VirtError::last_virt_error())),
+            _ => Ok(()),
+        }
+    }
+}
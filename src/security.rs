@@ -0,0 +1,146 @@
+use crate::{
+    typed_params::TypedParams, util::copy_string, Connection, Domain, Error, VirtError, Wrapper,
+};
+use std::{mem, ptr, slice};
+
+/// The security label a domain is confined with, as applied by a security driver such as
+/// SELinux or AppArmor.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct SecurityLabel {
+    /// The label, such as `system_u:system_r:svirt_t:s0:c87,c520` for SELinux. Empty if the
+    /// domain is not confined.
+    pub label: String,
+    /// Whether the security driver enforces the label, as opposed to only reporting violations.
+    pub enforcing: bool,
+}
+
+impl SecurityLabel {
+    fn from_raw(label: &virt_sys::virSecurityLabel) -> Result<Self, Error> {
+        Ok(SecurityLabel {
+            label: unsafe { copy_string(label.label.as_ptr()) }?,
+            enforcing: label.enforcing != 0,
+        })
+    }
+}
+
+/// The security model of a host, as returned by [Connection::node_security_model].
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct SecurityModel {
+    /// The name of the security driver, such as `selinux`. Empty if no security driver is
+    /// active.
+    pub model: String,
+    /// The domain of interpretation of the security labels.
+    pub doi: String,
+}
+
+/// The AMD Secure Encrypted Virtualization capabilities of a host, as returned by
+/// [Connection::node_sev_info].
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct SevInfo {
+    /// The platform Diffie-Hellman key, base64 encoded.
+    pub pdh: String,
+    /// The platform certificate chain, base64 encoded.
+    pub cert_chain: String,
+    /// The position of the encryption bit in page table entries.
+    pub cbitpos: u32,
+    /// The number of physical address bits lost when memory encryption is enabled.
+    pub reduced_phys_bits: u32,
+}
+
+/// Information about the launch security of a domain, as returned by
+/// [Domain::launch_security_info].
+#[derive(Debug, Clone, Default, Eq, PartialEq, Hash)]
+pub struct LaunchSecurityInfo {
+    /// The measurement of the initial memory of a domain running with AMD SEV, base64 encoded.
+    /// Used by the guest owner to verify the domain was launched as expected.
+    pub sev_measurement: Option<String>,
+}
+
+impl Domain {
+    /// Returns the security label of the domain, as applied by the primary security driver.
+    pub fn security_label(&self) -> Result<SecurityLabel, Error> {
+        let mut label: virt_sys::virSecurityLabel = unsafe { mem::zeroed() };
+        match unsafe { virt_sys::virDomainGetSecurityLabel(self.as_ptr(), &mut label) } {
+            -1 => Err(Error::from(VirtError::last_virt_error())),
+            _ => SecurityLabel::from_raw(&label),
+        }
+    }
+
+    /// Returns the security labels of the domain, one for each active security driver.
+    pub fn security_label_list(&self) -> Result<Vec<SecurityLabel>, Error> {
+        let mut labels = ptr::null_mut();
+        let n = match unsafe { virt_sys::virDomainGetSecurityLabelList(self.as_ptr(), &mut labels) }
+        {
+            -1 => return Err(Error::from(VirtError::last_virt_error())),
+            n => n as usize,
+        };
+        if labels.is_null() {
+            return Ok(Vec::new());
+        }
+        let result = unsafe { slice::from_raw_parts(labels, n) }
+            .iter()
+            .map(SecurityLabel::from_raw)
+            .collect();
+        unsafe { libc::free(labels as *mut _) };
+        result
+    }
+
+    /// Returns information about the launch security of the domain. The domain must be
+    /// running.
+    pub fn launch_security_info(&self) -> Result<LaunchSecurityInfo, Error> {
+        let mut params = ptr::null_mut();
+        let mut nparams = 0;
+        match unsafe {
+            virt_sys::virDomainGetLaunchSecurityInfo(self.as_ptr(), &mut params, &mut nparams, 0)
+        } {
+            -1 => Err(Error::from(VirtError::last_virt_error())),
+            _ => {
+                let params = unsafe { TypedParams::from_raw(params, nparams) };
+                Ok(LaunchSecurityInfo {
+                    sev_measurement: params
+                        .get_string(virt_sys::VIR_DOMAIN_LAUNCH_SECURITY_SEV_MEASUREMENT)?,
+                })
+            }
+        }
+    }
+}
+
+impl Connection {
+    /// Returns the security model of the host.
+    pub fn node_security_model(&self) -> Result<SecurityModel, Error> {
+        let mut model: virt_sys::virSecurityModel = unsafe { mem::zeroed() };
+        match unsafe { virt_sys::virNodeGetSecurityModel(self.as_ptr(), &mut model) } {
+            -1 => Err(Error::from(VirtError::last_virt_error())),
+            _ => Ok(SecurityModel {
+                model: unsafe { copy_string(model.model.as_ptr()) }?,
+                doi: unsafe { copy_string(model.doi.as_ptr()) }?,
+            }),
+        }
+    }
+
+    /// Returns the AMD SEV capabilities of the host. Fails if the host does not support SEV.
+    pub fn node_sev_info(&self) -> Result<SevInfo, Error> {
+        let mut params = ptr::null_mut();
+        let mut nparams = 0;
+        match unsafe { virt_sys::virNodeGetSEVInfo(self.as_ptr(), &mut params, &mut nparams, 0) } {
+            -1 => Err(Error::from(VirtError::last_virt_error())),
+            _ => {
+                let params = unsafe { TypedParams::from_raw(params, nparams) };
+                Ok(SevInfo {
+                    pdh: params
+                        .get_string(virt_sys::VIR_NODE_SEV_PDH)?
+                        .unwrap_or_default(),
+                    cert_chain: params
+                        .get_string(virt_sys::VIR_NODE_SEV_CERT_CHAIN)?
+                        .unwrap_or_default(),
+                    cbitpos: params
+                        .get_uint(virt_sys::VIR_NODE_SEV_CBITPOS)?
+                        .unwrap_or(0),
+                    reduced_phys_bits: params
+                        .get_uint(virt_sys::VIR_NODE_SEV_REDUCED_PHYS_BITS)?
+                        .unwrap_or(0),
+                })
+            }
+        }
+    }
+}
@@ -0,0 +1,280 @@
+use crate::{
+    domain::XmlFlags,
+    util::{take_string, take_wrapper_array},
+    Domain, Error, VirtError, Wrapper,
+};
+use std::{
+    ffi::{CStr, CString},
+    mem, ptr,
+};
+
+bitflags::bitflags! {
+    /// Flags affecting how a snapshot is created with [Domain::create_snapshot].
+    pub struct SnapshotCreateFlags: u32 {
+        /// Restore or alter the metadata of an existing snapshot, instead of taking a new one.
+        const REDEFINE = virt_sys::VIR_DOMAIN_SNAPSHOT_CREATE_REDEFINE;
+        /// With [SnapshotCreateFlags::REDEFINE], make the snapshot the current snapshot.
+        const CURRENT = virt_sys::VIR_DOMAIN_SNAPSHOT_CREATE_CURRENT;
+        /// Take the snapshot without keeping any libvirt metadata about it.
+        const NO_METADATA = virt_sys::VIR_DOMAIN_SNAPSHOT_CREATE_NO_METADATA;
+        /// Stop the domain after the snapshot has been taken.
+        const HALT = virt_sys::VIR_DOMAIN_SNAPSHOT_CREATE_HALT;
+        /// Only snapshot the disks, not the memory state of the domain.
+        const DISK_ONLY = virt_sys::VIR_DOMAIN_SNAPSHOT_CREATE_DISK_ONLY;
+        /// Reuse any existing external files for the snapshot.
+        const REUSE_EXT = virt_sys::VIR_DOMAIN_SNAPSHOT_CREATE_REUSE_EXT;
+        /// Use the guest agent to freeze the guest file systems while the snapshot is taken.
+        const QUIESCE = virt_sys::VIR_DOMAIN_SNAPSHOT_CREATE_QUIESCE;
+        /// Either snapshot all disks or none of them.
+        const ATOMIC = virt_sys::VIR_DOMAIN_SNAPSHOT_CREATE_ATOMIC;
+        /// Take the snapshot while the domain keeps running.
+        const LIVE = virt_sys::VIR_DOMAIN_SNAPSHOT_CREATE_LIVE;
+    }
+}
+
+bitflags::bitflags! {
+    /// Flags filtering the snapshots returned by [Domain::list_all_snapshots] and
+    /// [DomainSnapshot::children]. Within each group of related flags, no flags means no
+    /// filtering on that property.
+    pub struct SnapshotListFlags: u32 {
+        /// With [Domain::list_all_snapshots], only list snapshots without a parent.
+        const ROOTS = virt_sys::VIR_DOMAIN_SNAPSHOT_LIST_ROOTS;
+        /// With [DomainSnapshot::children], list all descendants instead of only direct
+        /// children.
+        const DESCENDANTS = virt_sys::VIR_DOMAIN_SNAPSHOT_LIST_DESCENDANTS;
+        /// Only list snapshots without children.
+        const LEAVES = virt_sys::VIR_DOMAIN_SNAPSHOT_LIST_LEAVES;
+        /// Only list snapshots with children.
+        const NO_LEAVES = virt_sys::VIR_DOMAIN_SNAPSHOT_LIST_NO_LEAVES;
+        /// Only list snapshots with libvirt metadata.
+        const METADATA = virt_sys::VIR_DOMAIN_SNAPSHOT_LIST_METADATA;
+        /// Only list snapshots without libvirt metadata.
+        const NO_METADATA = virt_sys::VIR_DOMAIN_SNAPSHOT_LIST_NO_METADATA;
+        /// Only list snapshots taken while the domain was shut off.
+        const INACTIVE = virt_sys::VIR_DOMAIN_SNAPSHOT_LIST_INACTIVE;
+        /// Only list snapshots taken while the domain was running.
+        const ACTIVE = virt_sys::VIR_DOMAIN_SNAPSHOT_LIST_ACTIVE;
+        /// Only list disk only snapshots.
+        const DISK_ONLY = virt_sys::VIR_DOMAIN_SNAPSHOT_LIST_DISK_ONLY;
+        /// Only list snapshots stored inside the disk images.
+        const INTERNAL = virt_sys::VIR_DOMAIN_SNAPSHOT_LIST_INTERNAL;
+        /// Only list snapshots stored in separate files.
+        const EXTERNAL = virt_sys::VIR_DOMAIN_SNAPSHOT_LIST_EXTERNAL;
+    }
+}
+
+bitflags::bitflags! {
+    /// Flags affecting [Domain::revert_to_snapshot].
+    pub struct RevertFlags: u32 {
+        /// Run the domain after reverting, regardless of its state in the snapshot.
+        const RUNNING = virt_sys::VIR_DOMAIN_SNAPSHOT_REVERT_RUNNING;
+        /// Pause the domain after reverting, regardless of its state in the snapshot.
+        const PAUSED = virt_sys::VIR_DOMAIN_SNAPSHOT_REVERT_PAUSED;
+        /// Allow risky reverts, such as ones that require restarting the domain.
+        const FORCE = virt_sys::VIR_DOMAIN_SNAPSHOT_REVERT_FORCE;
+    }
+}
+
+bitflags::bitflags! {
+    /// Flags affecting [DomainSnapshot::delete].
+    pub struct SnapshotDeleteFlags: u32 {
+        /// Also delete all children of the snapshot.
+        const CHILDREN = virt_sys::VIR_DOMAIN_SNAPSHOT_DELETE_CHILDREN;
+        /// Only delete the libvirt metadata, leaving the snapshot data in place.
+        const METADATA_ONLY = virt_sys::VIR_DOMAIN_SNAPSHOT_DELETE_METADATA_ONLY;
+        /// Only delete the children of the snapshot, keeping the snapshot itself.
+        const CHILDREN_ONLY = virt_sys::VIR_DOMAIN_SNAPSHOT_DELETE_CHILDREN_ONLY;
+    }
+}
+
+/// A snapshot of the disk and/or memory state of a [Domain].
+pub struct DomainSnapshot(virt_sys::virDomainSnapshotPtr);
+
+// Safety: libvirt is thread safe since 0.6.0. It can handle multiple threads making calls to the
+// same virDomainSnapshot instance.
+unsafe impl Send for DomainSnapshot {}
+unsafe impl Sync for DomainSnapshot {}
+
+impl DomainSnapshot {
+    /// Returns the name of the snapshot.
+    pub fn name(&self) -> Result<String, Error> {
+        let name_ptr = cvt_null!(unsafe { virt_sys::virDomainSnapshotGetName(self.0) })?;
+        unsafe { CStr::from_ptr(name_ptr) }
+            .to_str()
+            .map(str::to_owned)
+            .map_err(Error::Utf8Error)
+    }
+
+    /// Returns the parent of the snapshot, or `None` if it is a root snapshot.
+    pub fn parent(&self) -> Result<Option<DomainSnapshot>, VirtError> {
+        no_snapshot_to_none(cvt_null!(unsafe {
+            virt_sys::virDomainSnapshotGetParent(self.0, 0)
+        }))
+    }
+
+    /// Returns the XML description of the snapshot. Only [XmlFlags::SECURE] is supported, it
+    /// includes security sensitive information in the XML.
+    pub fn xml_desc(&self, flags: XmlFlags) -> Result<String, Error> {
+        let xml_ptr =
+            cvt_null!(unsafe { virt_sys::virDomainSnapshotGetXMLDesc(self.0, flags.bits()) })?;
+        unsafe { take_string(xml_ptr) }
+    }
+
+    /// Returns whether this is the current snapshot of its domain.
+    pub fn is_current(&self) -> Result<bool, VirtError> {
+        match unsafe { virt_sys::virDomainSnapshotIsCurrent(self.0, 0) } {
+            -1 => Err(VirtError::last_virt_error()),
+            0 => Ok(false),
+            _ => Ok(true),
+        }
+    }
+
+    /// Returns whether libvirt has metadata about this snapshot. Snapshots without metadata
+    /// are not deleted when the domain is undefined.
+    pub fn has_metadata(&self) -> Result<bool, VirtError> {
+        match unsafe { virt_sys::virDomainSnapshotHasMetadata(self.0, 0) } {
+            -1 => Err(VirtError::last_virt_error()),
+            0 => Ok(false),
+            _ => Ok(true),
+        }
+    }
+
+    /// Returns the children of this snapshot, filtered by `flags`. Use
+    /// [SnapshotListFlags::DESCENDANTS] to get all descendants.
+    pub fn children(&self, flags: SnapshotListFlags) -> Result<Vec<DomainSnapshot>, VirtError> {
+        let mut snapshots = ptr::null_mut();
+        match unsafe {
+            virt_sys::virDomainSnapshotListAllChildren(self.0, &mut snapshots, flags.bits())
+        } {
+            -1 => Err(VirtError::last_virt_error()),
+            n => Ok(unsafe { take_wrapper_array(snapshots, n as usize) }),
+        }
+    }
+
+    /// Delete the snapshot. Unless [SnapshotDeleteFlags::CHILDREN] is given, its children are
+    /// re-parented to the parent of this snapshot. The snapshot object should not be used for
+    /// anything but freeing after this.
+    pub fn delete(&self, flags: SnapshotDeleteFlags) -> Result<(), VirtError> {
+        match unsafe { virt_sys::virDomainSnapshotDelete(self.0, flags.bits()) } {
+            -1 => Err(VirtError::last_virt_error()),
+            _ => Ok(()),
+        }
+    }
+
+    /// Free the snapshot object. The snapshot itself is kept.
+    /// If this is not explicitly called it will be called by the `Drop` implementation. And any
+    /// error will be logged to the error level.
+    ///
+    /// The only reason to call this explicitly is if you want to handle the error in some other
+    /// way than just logging it.
+    pub fn free(self) -> Result<(), VirtError> {
+        let result = self.free_internal();
+        mem::forget(self);
+        result
+    }
+
+    fn free_internal(&self) -> Result<(), VirtError> {
+        match unsafe { virt_sys::virDomainSnapshotFree(self.0) } {
+            -1 => Err(VirtError::last_virt_error()),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Converts the result of a snapshot lookup into `None` if the snapshot does not exist.
+fn no_snapshot_to_none(
+    result: Result<virt_sys::virDomainSnapshotPtr, VirtError>,
+) -> Result<Option<DomainSnapshot>, VirtError> {
+    match result {
+        Ok(ptr) => Ok(Some(DomainSnapshot(ptr))),
+        Err(e) if e.is_code(virt_sys::VIR_ERR_NO_DOMAIN_SNAPSHOT) => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+impl Domain {
+    /// Take a snapshot of the domain, as described by the snapshot XML. The XML may be as
+    /// small as `<domainsnapshot/>`, in which case libvirt picks a name and the defaults.
+    pub fn create_snapshot(
+        &self,
+        xml: &str,
+        flags: SnapshotCreateFlags,
+    ) -> Result<DomainSnapshot, Error> {
+        let xml_cstr = CString::new(xml).map_err(Error::InvalidXml)?;
+        let ptr = cvt_null!(unsafe {
+            virt_sys::virDomainSnapshotCreateXML(self.as_ptr(), xml_cstr.as_ptr(), flags.bits())
+        })?;
+        Ok(DomainSnapshot(ptr))
+    }
+
+    /// Returns all snapshots of the domain, filtered by `flags`.
+    pub fn list_all_snapshots(
+        &self,
+        flags: SnapshotListFlags,
+    ) -> Result<Vec<DomainSnapshot>, VirtError> {
+        let mut snapshots = ptr::null_mut();
+        match unsafe {
+            virt_sys::virDomainListAllSnapshots(self.as_ptr(), &mut snapshots, flags.bits())
+        } {
+            -1 => Err(VirtError::last_virt_error()),
+            n => Ok(unsafe { take_wrapper_array(snapshots, n as usize) }),
+        }
+    }
+
+    /// Look up a snapshot of the domain by its name.
+    pub fn snapshot_by_name(&self, name: &str) -> Result<DomainSnapshot, Error> {
+        let name_cstr = CString::new(name).map_err(Error::InvalidName)?;
+        let ptr = cvt_null!(unsafe {
+            virt_sys::virDomainSnapshotLookupByName(self.as_ptr(), name_cstr.as_ptr(), 0)
+        })?;
+        Ok(DomainSnapshot(ptr))
+    }
+
+    /// Returns the current snapshot of the domain, or `None` if it has no current snapshot.
+    pub fn current_snapshot(&self) -> Result<Option<DomainSnapshot>, VirtError> {
+        no_snapshot_to_none(cvt_null!(unsafe {
+            virt_sys::virDomainSnapshotCurrent(self.as_ptr(), 0)
+        }))
+    }
+
+    /// Revert the domain to the state recorded in `snapshot`. By default the domain ends up in
+    /// the state it was in when the snapshot was taken, see [RevertFlags] to override this.
+    pub fn revert_to_snapshot(
+        &self,
+        snapshot: &DomainSnapshot,
+        flags: RevertFlags,
+    ) -> Result<(), VirtError> {
+        match unsafe { virt_sys::virDomainRevertToSnapshot(snapshot.0, flags.bits()) } {
+            -1 => Err(VirtError::last_virt_error()),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl crate::Wrapper for DomainSnapshot {
+    type Ptr = virt_sys::virDomainSnapshotPtr;
+
+    unsafe fn from_ptr(ptr: Self::Ptr) -> Self {
+        Self(ptr)
+    }
+
+    fn as_ptr(&self) -> Self::Ptr {
+        self.0
+    }
+}
+
+impl Clone for DomainSnapshot {
+    fn clone(&self) -> Self {
+        let ret = unsafe { virt_sys::virDomainSnapshotRef(self.0) };
+        assert_eq!(ret, 0, "Unexpected error from virDomainSnapshotRef");
+        Self(self.0)
+    }
+}
+
+impl Drop for DomainSnapshot {
+    fn drop(&mut self) {
+        if let Err(e) = self.free_internal() {
+            log::error!("Error when freeing domain snapshot: {}", e);
+        }
+    }
+}
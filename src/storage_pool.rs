@@ -0,0 +1,92 @@
+use crate::{util::copy_string, Connection, Error, VirtError, Wrapper};
+use std::{ffi::CString, mem, os::raw::c_int};
+
+/// A pool of storage managed by libvirt, such as a directory or an LVM volume group, that
+/// storage volumes are allocated from.
+pub struct StoragePool(virt_sys::virStoragePoolPtr);
+
+// Safety: libvirt is thread safe since 0.6.0. It can handle multiple threads making calls to the
+// same virStoragePool instance.
+unsafe impl Send for StoragePool {}
+unsafe impl Sync for StoragePool {}
+
+impl StoragePool {
+    /// See [Connection::storage_pool_by_name].
+    pub(crate) fn lookup_by_name(connection: &Connection, name: &str) -> Result<Self, Error> {
+        let name_cstr = CString::new(name).map_err(Error::InvalidName)?;
+        let ptr = cvt_null!(unsafe {
+            virt_sys::virStoragePoolLookupByName(connection.as_ptr(), name_cstr.as_ptr())
+        })?;
+        Ok(StoragePool(ptr))
+    }
+
+    /// Returns the name of the storage pool.
+    pub fn name(&self) -> Result<String, Error> {
+        let name_ptr = cvt_null!(unsafe { virt_sys::virStoragePoolGetName(self.0) })?;
+        unsafe { copy_string(name_ptr) }
+    }
+
+    /// Returns whether the storage pool is started automatically when the host boots.
+    pub fn autostart(&self) -> Result<bool, VirtError> {
+        let mut autostart = 0;
+        match unsafe { virt_sys::virStoragePoolGetAutostart(self.0, &mut autostart) } {
+            -1 => Err(VirtError::last_virt_error()),
+            _ => Ok(autostart != 0),
+        }
+    }
+
+    /// Set whether the storage pool is started automatically when the host boots.
+    pub fn set_autostart(&self, autostart: bool) -> Result<(), VirtError> {
+        match unsafe { virt_sys::virStoragePoolSetAutostart(self.0, autostart as c_int) } {
+            -1 => Err(VirtError::last_virt_error()),
+            _ => Ok(()),
+        }
+    }
+
+    /// Free the storage pool object. The storage pool itself is not affected.
+    /// If this is not explicitly called it will be called by the `Drop` implementation. And any
+    /// error will be logged to the error level.
+    ///
+    /// The only reason to call this explicitly is if you want to handle the error in some other
+    /// way than just logging it.
+    pub fn free(self) -> Result<(), VirtError> {
+        let result = self.free_internal();
+        mem::forget(self);
+        result
+    }
+
+    fn free_internal(&self) -> Result<(), VirtError> {
+        match unsafe { virt_sys::virStoragePoolFree(self.0) } {
+            -1 => Err(VirtError::last_virt_error()),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl crate::Wrapper for StoragePool {
+    type Ptr = virt_sys::virStoragePoolPtr;
+
+    unsafe fn from_ptr(ptr: Self::Ptr) -> Self {
+        Self(ptr)
+    }
+
+    fn as_ptr(&self) -> Self::Ptr {
+        self.0
+    }
+}
+
+impl Clone for StoragePool {
+    fn clone(&self) -> Self {
+        let ret = unsafe { virt_sys::virStoragePoolRef(self.0) };
+        assert_eq!(ret, 0, "Unexpected error from virStoragePoolRef");
+        Self(self.0)
+    }
+}
+
+impl Drop for StoragePool {
+    fn drop(&mut self) {
+        if let Err(e) = self.free_internal() {
+            log::error!("Error when freeing storage pool: {}", e);
+        }
+    }
+}
@@ -0,0 +1,706 @@
+use crate::{Connection, Error, VirtError, Wrapper};
+use std::{
+    any::Any,
+    convert::TryFrom,
+    ffi::c_void,
+    fs::File,
+    io::{self, Read, Seek, SeekFrom, Write},
+    mem,
+    os::{
+        raw::{c_char, c_int, c_longlong},
+        unix::io::{AsRawFd, RawFd},
+    },
+    panic::{self, AssertUnwindSafe},
+    slice,
+};
+
+#[cfg(feature = "tokio")]
+mod async_stream;
+#[cfg(feature = "tokio")]
+pub use async_stream::AsyncStream;
+
+bitflags::bitflags! {
+    /// Flags affecting the creation of a [Stream].
+    pub struct StreamFlags: u32 {
+        /// Make the stream non-blocking. Transfers that can't make progress immediately return
+        /// `Ok(None)`, or `io::ErrorKind::WouldBlock` via the `std::io` traits, instead of
+        /// blocking. Use [Stream::add_event_callback] to get notified when to try again.
+        const NONBLOCK = virt_sys::VIR_STREAM_NONBLOCK;
+    }
+}
+
+bitflags::bitflags! {
+    /// Events that can be watched for on a [Stream] with [Stream::add_event_callback].
+    #[derive(Default)]
+    pub struct StreamEvents: u32 {
+        /// Data can be read from the stream without blocking.
+        const READABLE = virt_sys::VIR_STREAM_EVENT_READABLE;
+        /// Data can be written to the stream without blocking.
+        const WRITABLE = virt_sys::VIR_STREAM_EVENT_WRITABLE;
+        /// An error occurred on the stream.
+        const ERROR = virt_sys::VIR_STREAM_EVENT_ERROR;
+        /// The other end of the stream hung up.
+        const HANGUP = virt_sys::VIR_STREAM_EVENT_HANGUP;
+    }
+}
+
+bitflags::bitflags! {
+    /// Flags affecting how data is received with [Stream::recv_flags].
+    pub struct RecvFlags: u32 {
+        /// Stop receiving when a hole is reached in a sparse stream, instead of returning the
+        /// hole as a block of zeroes. The hole can then be consumed with [Stream::recv_hole].
+        const STOP_AT_HOLE = virt_sys::VIR_STREAM_RECV_STOP_AT_HOLE;
+    }
+}
+
+/// The outcome of a successful [Stream::recv_flags] call.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum SparseRecv {
+    /// This many bytes of data were read. Zero means the end of the stream has been reached.
+    Data(usize),
+    /// The stream is positioned at a hole. Its length can be read with [Stream::recv_hole].
+    Hole,
+}
+
+/// A section of a sparse data source, as reported to [Stream::sparse_send_all]. Each variant
+/// holds the number of bytes left of the section, counted from the current position.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum SparseSection {
+    /// The current position is in a section containing data.
+    Data(u64),
+    /// The current position is in a hole.
+    Hole(u64),
+}
+
+/// A data stream to or from the hypervisor. Streams are used to transfer data such as
+/// screenshots, console I/O and storage volume contents.
+///
+/// A stream is created unattached. It is attached to some data source or sink by the API
+/// it is handed to, such as [Domain::screenshot](crate::Domain::screenshot). Data is then
+/// transferred with [Stream::send] and [Stream::recv], or via the [io::Read] and [io::Write]
+/// implementations. When all data has been transferred the stream must be completed with
+/// [Stream::finish]. A stream that is dropped without being finished is aborted.
+pub struct Stream {
+    ptr: virt_sys::virStreamPtr,
+    state: State,
+}
+
+/// What is known about the transfer a [Stream] is attached to, deciding how it is dropped.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum State {
+    /// The stream was created by this library and has not been handed to a crate API that
+    /// attaches it. The caller might still have attached it through the raw pointer.
+    Unattached,
+    /// The stream is attached to a transfer that has neither finished nor been aborted.
+    Active,
+    /// libvirt has already aborted the transfer.
+    Aborted,
+}
+
+// Safety: libvirt is thread safe since 0.6.0. It can handle multiple threads making calls to the
+// same virStream instance.
+unsafe impl Send for Stream {}
+unsafe impl Sync for Stream {}
+
+impl Stream {
+    /// Creates a new, blocking, stream on the given connection.
+    pub fn new(connection: &Connection) -> Result<Self, VirtError> {
+        Self::new_raw(connection.as_ptr(), 0)
+    }
+
+    /// Creates a new stream on the given connection, with the given flags.
+    pub fn with_flags(connection: &Connection, flags: StreamFlags) -> Result<Self, VirtError> {
+        Self::new_raw(connection.as_ptr(), flags.bits())
+    }
+
+    pub(crate) fn new_raw(
+        connection_ptr: virt_sys::virConnectPtr,
+        flags: virt_sys::virStreamFlags,
+    ) -> Result<Self, VirtError> {
+        let ptr = cvt_null!(unsafe { virt_sys::virStreamNew(connection_ptr, flags) })?;
+        Ok(Stream {
+            ptr,
+            state: State::Unattached,
+        })
+    }
+
+    /// Records that the stream has been attached to a transfer, so a failure to abort it when
+    /// dropped unfinished is logged. Should be called once the stream has been handed to
+    /// libvirt.
+    pub(crate) fn set_active(&mut self) {
+        self.state = State::Active;
+    }
+
+    /// Write a series of bytes to the stream. Returns the number of bytes written, which might
+    /// be less than `data.len()`. Returns `Ok(None)` if the stream is non-blocking and no data
+    /// could be written without blocking.
+    pub fn send(&mut self, data: &[u8]) -> Result<Option<usize>, VirtError> {
+        let len = data.len().min(c_int::MAX as usize);
+        match unsafe { virt_sys::virStreamSend(self.ptr, data.as_ptr() as *const c_char, len) } {
+            -1 => Err(VirtError::last_virt_error()),
+            -2 => Ok(None),
+            n => Ok(Some(n as usize)),
+        }
+    }
+
+    /// Read a series of bytes from the stream into `buf`. Returns the number of bytes read,
+    /// which is zero when the end of the stream has been reached. Returns `Ok(None)` if the
+    /// stream is non-blocking and no data is available without blocking.
+    pub fn recv(&mut self, buf: &mut [u8]) -> Result<Option<usize>, VirtError> {
+        let len = buf.len().min(c_int::MAX as usize);
+        match unsafe { virt_sys::virStreamRecv(self.ptr, buf.as_mut_ptr() as *mut c_char, len) } {
+            -1 => Err(VirtError::last_virt_error()),
+            -2 => Ok(None),
+            n => Ok(Some(n as usize)),
+        }
+    }
+
+    /// Same as [Stream::recv], but with the possibility to pass [RecvFlags::STOP_AT_HOLE]. With
+    /// that flag the method returns [SparseRecv::Hole] when the stream reaches a hole, instead
+    /// of returning the hole as zeroes.
+    pub fn recv_flags(
+        &mut self,
+        buf: &mut [u8],
+        flags: RecvFlags,
+    ) -> Result<Option<SparseRecv>, VirtError> {
+        let len = buf.len().min(c_int::MAX as usize);
+        match unsafe {
+            virt_sys::virStreamRecvFlags(
+                self.ptr,
+                buf.as_mut_ptr() as *mut c_char,
+                len,
+                flags.bits(),
+            )
+        } {
+            -1 => Err(VirtError::last_virt_error()),
+            -2 => Ok(None),
+            -3 => Ok(Some(SparseRecv::Hole)),
+            n => Ok(Some(SparseRecv::Data(n as usize))),
+        }
+    }
+
+    /// Consume the hole the stream is positioned at and return its length in bytes. Should be
+    /// called after [Stream::recv_flags] has returned [SparseRecv::Hole].
+    pub fn recv_hole(&mut self) -> Result<u64, VirtError> {
+        let mut length: c_longlong = 0;
+        match unsafe { virt_sys::virStreamRecvHole(self.ptr, &mut length, 0) } {
+            -1 => Err(VirtError::last_virt_error()),
+            _ => Ok(length as u64),
+        }
+    }
+
+    /// Tell the other end of the stream that the next `length` bytes are a hole. Only valid on
+    /// streams that were opened as sparse by the API they were handed to. Fails with
+    /// [Error::Io] if `length` does not fit in an `i64`.
+    pub fn send_hole(&mut self, length: u64) -> Result<(), Error> {
+        let length = seek_offset(length).map_err(Error::Io)?;
+        match unsafe { virt_sys::virStreamSendHole(self.ptr, length, 0) } {
+            -1 => Err(Error::from(VirtError::last_virt_error())),
+            _ => Ok(()),
+        }
+    }
+
+    /// Send an entire sparse data source over the stream. libvirt drives the transfer by
+    /// calling the given closures:
+    ///
+    /// * `hole` must return the [SparseSection] at the current position of the source.
+    /// * `data` must fill the buffer with data from the current position and return the
+    ///   number of bytes written to it. Returning zero signals the end of the source and ends
+    ///   the transfer, even in the middle of a data section.
+    /// * `skip` must move the current position forward by the given number of bytes. It is
+    ///   called after a hole has been sent.
+    ///
+    /// If the transfer fails the stream is aborted, and an error returned by a closure is
+    /// returned as [Error::Io]. The stream must still be completed with [Stream::finish] after
+    /// a successful transfer.
+    pub fn sparse_send_all<F, H, S>(&mut self, data: F, hole: H, skip: S) -> Result<(), Error>
+    where
+        F: FnMut(&mut [u8]) -> io::Result<usize>,
+        H: FnMut() -> io::Result<SparseSection>,
+        S: FnMut(u64) -> io::Result<()>,
+    {
+        let mut source = SparseSource {
+            data,
+            hole,
+            skip,
+            error: None,
+        };
+        let ret = unsafe {
+            virt_sys::virStreamSparseSendAll(
+                self.ptr,
+                Some(sparse_source_data::<F, H, S>),
+                Some(sparse_source_hole::<F, H, S>),
+                Some(sparse_source_skip::<F, H, S>),
+                &mut source as *mut _ as *mut c_void,
+            )
+        };
+        if ret == -1 {
+            // libvirt aborts the stream itself when the transfer fails.
+            self.state = State::Aborted;
+        }
+        callback_result(ret, source.error)
+    }
+
+    /// Receive all data from a sparse stream. libvirt drives the transfer by calling the given
+    /// closures:
+    ///
+    /// * `data` is handed the next chunk of data and must return how many bytes of it were
+    ///   consumed. It is called again with the remainder until the whole chunk is consumed.
+    ///   Consuming nothing is treated as an error of kind `io::ErrorKind::WriteZero`.
+    /// * `hole` is told that the next given number of bytes are a hole.
+    ///
+    /// If the transfer fails the stream is aborted, and an error returned by a closure is
+    /// returned as [Error::Io]. The stream must still be completed with [Stream::finish] after
+    /// a successful transfer.
+    pub fn sparse_recv_all<F, H>(&mut self, data: F, hole: H) -> Result<(), Error>
+    where
+        F: FnMut(&[u8]) -> io::Result<usize>,
+        H: FnMut(u64) -> io::Result<()>,
+    {
+        let mut sink = SparseSink {
+            data,
+            hole,
+            error: None,
+        };
+        let ret = unsafe {
+            virt_sys::virStreamSparseRecvAll(
+                self.ptr,
+                Some(sparse_sink_data::<F, H>),
+                Some(sparse_sink_hole::<F, H>),
+                &mut sink as *mut _ as *mut c_void,
+            )
+        };
+        if ret == -1 {
+            // libvirt aborts the stream itself when the transfer fails.
+            self.state = State::Aborted;
+        }
+        callback_result(ret, sink.error)
+    }
+
+    /// Send the content of `file`, from its current position to the end, over the stream.
+    /// Holes in the file are detected with `SEEK_DATA`/`SEEK_HOLE` and sent as holes, so only
+    /// allocated data is transferred. The stream must still be completed with
+    /// [Stream::finish] afterwards.
+    pub fn send_sparse_file(&mut self, file: &mut File) -> Result<(), Error> {
+        let file: &File = file;
+        self.sparse_send_all(
+            |buf| {
+                let mut file = file;
+                file.read(buf)
+            },
+            || file_section(file.as_raw_fd()),
+            |length| {
+                let mut file = file;
+                file.seek(SeekFrom::Current(seek_offset(length)?))
+                    .map(|_| ())
+            },
+        )
+    }
+
+    /// Replace the content of `file` with all data received on the stream. The file is
+    /// truncated first, and holes in the stream are recreated as holes in the file. The stream
+    /// must still be completed with [Stream::finish] afterwards.
+    pub fn recv_sparse_file(&mut self, file: &mut File) -> Result<(), Error> {
+        file.set_len(0).map_err(Error::Io)?;
+        file.seek(SeekFrom::Start(0)).map_err(Error::Io)?;
+        let file: &File = file;
+        self.sparse_recv_all(
+            |buf| {
+                let mut file = file;
+                file.write(buf)
+            },
+            |length| {
+                // Data is written sequentially into the truncated file, so skipping over the
+                // hole always moves past the end of the file. The gap reads back as zeroes.
+                let mut file = file;
+                file.seek(SeekFrom::Current(seek_offset(length)?))
+                    .map(|_| ())
+            },
+        )?;
+        // Extend the file in case it ends with a hole.
+        let mut file = file;
+        let end = file.stream_position().map_err(Error::Io)?;
+        file.set_len(end).map_err(Error::Io)
+    }
+
+    /// Register a callback to be invoked when any of the given `events` occur on the stream.
+    /// Only one callback can be registered per stream. The callback is invoked from the
+    /// libvirt event loop, so an event loop implementation must have been registered before
+    /// the connection was opened, see [crate::event]. This is mostly useful together with
+    /// [StreamFlags::NONBLOCK].
+    ///
+    /// The callback is dropped when it is removed with [Stream::remove_event_callback], or when
+    /// the stream is finished or aborted.
+    pub fn add_event_callback<F>(
+        &mut self,
+        events: StreamEvents,
+        mut callback: F,
+    ) -> Result<(), VirtError>
+    where
+        F: FnMut(StreamEvents) + Send + 'static,
+    {
+        self.add_event_callback_raw(events, move |_stream, events| callback(events))
+    }
+
+    /// Same as [Stream::add_event_callback], but the callback is also handed the raw stream
+    /// pointer. It stays valid for as long as the callback is registered.
+    pub(crate) fn add_event_callback_raw<F>(
+        &mut self,
+        events: StreamEvents,
+        callback: F,
+    ) -> Result<(), VirtError>
+    where
+        F: FnMut(virt_sys::virStreamPtr, StreamEvents) + Send + 'static,
+    {
+        let opaque = Box::into_raw(Box::new(callback));
+        match unsafe {
+            virt_sys::virStreamEventAddCallback(
+                self.ptr,
+                events.bits() as c_int,
+                Some(event_callback::<F>),
+                opaque as *mut c_void,
+                Some(free_event_callback::<F>),
+            )
+        } {
+            -1 => {
+                // libvirt does not take ownership of the callback when registration fails.
+                drop(unsafe { Box::from_raw(opaque) });
+                Err(VirtError::last_virt_error())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Change the set of events the callback registered with [Stream::add_event_callback] is
+    /// invoked for.
+    pub fn update_event_callback(&mut self, events: StreamEvents) -> Result<(), VirtError> {
+        match unsafe { virt_sys::virStreamEventUpdateCallback(self.ptr, events.bits() as c_int) } {
+            -1 => Err(VirtError::last_virt_error()),
+            _ => Ok(()),
+        }
+    }
+
+    /// Remove the callback registered with [Stream::add_event_callback].
+    pub fn remove_event_callback(&mut self) -> Result<(), VirtError> {
+        match unsafe { virt_sys::virStreamEventRemoveCallback(self.ptr) } {
+            -1 => Err(VirtError::last_virt_error()),
+            _ => Ok(()),
+        }
+    }
+
+    /// Indicate that there is no further data to be transmitted on the stream, then free it.
+    /// For output streams this should be called once all data has been written. For input
+    /// streams this should be called once [Stream::recv] has reported the end of the stream.
+    ///
+    /// This method is a synchronization point for all asynchronous errors, so if this returns
+    /// successfully the caller can be sure that the data has been successfully processed.
+    pub fn finish(self) -> Result<(), VirtError> {
+        let result = match unsafe { virt_sys::virStreamFinish(self.ptr) } {
+            -1 => Err(VirtError::last_virt_error()),
+            _ => Ok(()),
+        };
+        self.free_and_forget(result)
+    }
+
+    /// Request that the in progress data transfer be cancelled abnormally before the end of the
+    /// stream has been reached, then free the stream. This is done automatically by the `Drop`
+    /// implementation for streams that were never finished.
+    pub fn abort(self) -> Result<(), VirtError> {
+        let result = self.abort_internal();
+        self.free_and_forget(result)
+    }
+
+    fn abort_internal(&self) -> Result<(), VirtError> {
+        match unsafe { virt_sys::virStreamAbort(self.ptr) } {
+            -1 => Err(VirtError::last_virt_error()),
+            _ => Ok(()),
+        }
+    }
+
+    /// Frees the stream without running the `Drop` implementation. Returns `result`, unless it
+    /// is `Ok` and freeing failed.
+    fn free_and_forget(self, result: Result<(), VirtError>) -> Result<(), VirtError> {
+        let free_result = self.free_internal();
+        mem::forget(self);
+        result.and(free_result)
+    }
+
+    fn free_internal(&self) -> Result<(), VirtError> {
+        match unsafe { virt_sys::virStreamFree(self.ptr) } {
+            -1 => Err(VirtError::last_virt_error()),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl io::Read for Stream {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        io_result(self.recv(buf))
+    }
+}
+
+impl io::Write for Stream {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        io_result(self.send(buf))
+    }
+
+    /// Data is handed to libvirt directly on each write, so there is nothing to flush. Use
+    /// [Stream::finish] to make sure all data has been processed by the other end.
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Converts the result of [Stream::send] or [Stream::recv] into the shape `std::io` expects.
+fn io_result(result: Result<Option<usize>, VirtError>) -> io::Result<usize> {
+    match result {
+        Ok(Some(n)) => Ok(n),
+        Ok(None) => Err(io::ErrorKind::WouldBlock.into()),
+        Err(e) => Err(io_error(e)),
+    }
+}
+
+/// Wraps a libvirt error for the `std::io` traits. `io::Error::other` would need Rust 1.74.
+#[allow(clippy::io_other_error)]
+fn io_error(e: VirtError) -> io::Error {
+    io::Error::new(io::ErrorKind::Other, e)
+}
+
+/// Errors and panics caught inside closures called by libvirt. They can't be propagated through
+/// the C code, so they are stored and dealt with once libvirt returns.
+enum CallbackError {
+    Io(io::Error),
+    Panic(Box<dyn Any + Send>),
+}
+
+/// Runs a closure called from libvirt, storing any error or panic in `error`.
+fn catch_callback<T>(
+    error: &mut Option<CallbackError>,
+    f: impl FnOnce() -> io::Result<T>,
+) -> Option<T> {
+    match panic::catch_unwind(AssertUnwindSafe(f)) {
+        Ok(Ok(value)) => Some(value),
+        Ok(Err(e)) => {
+            *error = Some(CallbackError::Io(e));
+            None
+        }
+        Err(payload) => {
+            *error = Some(CallbackError::Panic(payload));
+            None
+        }
+    }
+}
+
+/// Converts the return value of a libvirt function driven by closures into a `Result`. Errors
+/// returned by the closures take precedence over the libvirt error, and panics are resumed.
+fn callback_result(ret: c_int, error: Option<CallbackError>) -> Result<(), Error> {
+    match error {
+        Some(CallbackError::Panic(payload)) => panic::resume_unwind(payload),
+        Some(CallbackError::Io(e)) => Err(Error::Io(e)),
+        None if ret == -1 => Err(Error::from(VirtError::last_virt_error())),
+        None => Ok(()),
+    }
+}
+
+struct SparseSource<F, H, S> {
+    data: F,
+    hole: H,
+    skip: S,
+    error: Option<CallbackError>,
+}
+
+unsafe extern "C" fn sparse_source_data<F, H, S>(
+    _stream: virt_sys::virStreamPtr,
+    data: *mut c_char,
+    nbytes: usize,
+    opaque: *mut c_void,
+) -> c_int
+where
+    F: FnMut(&mut [u8]) -> io::Result<usize>,
+{
+    let source = &mut *(opaque as *mut SparseSource<F, H, S>);
+    let buf = slice::from_raw_parts_mut(data as *mut u8, nbytes);
+    let f = &mut source.data;
+    // libvirt sends as many bytes from the buffer as it is told were filled.
+    let filled = || match f(buf)? {
+        n if n > nbytes => Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "Data callback filled more than the buffer",
+        )),
+        n => Ok(n),
+    };
+    catch_callback(&mut source.error, filled).map_or(-1, |n| n as c_int)
+}
+
+unsafe extern "C" fn sparse_source_hole<F, H, S>(
+    _stream: virt_sys::virStreamPtr,
+    in_data: *mut c_int,
+    length: *mut c_longlong,
+    opaque: *mut c_void,
+) -> c_int
+where
+    H: FnMut() -> io::Result<SparseSection>,
+{
+    let source = &mut *(opaque as *mut SparseSource<F, H, S>);
+    let f = &mut source.hole;
+    match catch_callback(&mut source.error, f) {
+        Some(section) => {
+            let (section_in_data, section_length) = match section {
+                SparseSection::Data(length) => (1, length),
+                SparseSection::Hole(length) => (0, length),
+            };
+            *in_data = section_in_data;
+            *length = section_length.min(c_longlong::MAX as u64) as c_longlong;
+            0
+        }
+        None => -1,
+    }
+}
+
+unsafe extern "C" fn sparse_source_skip<F, H, S>(
+    _stream: virt_sys::virStreamPtr,
+    length: c_longlong,
+    opaque: *mut c_void,
+) -> c_int
+where
+    S: FnMut(u64) -> io::Result<()>,
+{
+    let source = &mut *(opaque as *mut SparseSource<F, H, S>);
+    let f = &mut source.skip;
+    catch_callback(&mut source.error, || f(length as u64)).map_or(-1, |()| 0)
+}
+
+struct SparseSink<F, H> {
+    data: F,
+    hole: H,
+    error: Option<CallbackError>,
+}
+
+unsafe extern "C" fn sparse_sink_data<F, H>(
+    _stream: virt_sys::virStreamPtr,
+    data: *const c_char,
+    nbytes: usize,
+    opaque: *mut c_void,
+) -> c_int
+where
+    F: FnMut(&[u8]) -> io::Result<usize>,
+{
+    let sink = &mut *(opaque as *mut SparseSink<F, H>);
+    let buf = slice::from_raw_parts(data as *const u8, nbytes);
+    let f = &mut sink.data;
+    // libvirt keeps handing over the rest of the chunk until it has all been consumed, so
+    // consuming nothing would loop forever.
+    let consumed = || match f(buf)? {
+        0 if !buf.is_empty() => Err(io::ErrorKind::WriteZero.into()),
+        n => Ok(n),
+    };
+    catch_callback(&mut sink.error, consumed).map_or(-1, |n| n as c_int)
+}
+
+unsafe extern "C" fn sparse_sink_hole<F, H>(
+    _stream: virt_sys::virStreamPtr,
+    length: c_longlong,
+    opaque: *mut c_void,
+) -> c_int
+where
+    H: FnMut(u64) -> io::Result<()>,
+{
+    let sink = &mut *(opaque as *mut SparseSink<F, H>);
+    let f = &mut sink.hole;
+    catch_callback(&mut sink.error, || f(length as u64)).map_or(-1, |()| 0)
+}
+
+unsafe extern "C" fn event_callback<F>(
+    stream: virt_sys::virStreamPtr,
+    events: c_int,
+    opaque: *mut c_void,
+) where
+    F: FnMut(virt_sys::virStreamPtr, StreamEvents) + Send + 'static,
+{
+    let callback = &mut *(opaque as *mut F);
+    let events = StreamEvents::from_bits_truncate(events as u32);
+    // Unwinding into the libvirt event loop is not an option, and there is nobody to hand the
+    // panic to.
+    if panic::catch_unwind(AssertUnwindSafe(|| callback(stream, events))).is_err() {
+        log::error!("Stream event callback panicked");
+    }
+}
+
+unsafe extern "C" fn free_event_callback<F>(opaque: *mut c_void) {
+    drop(Box::from_raw(opaque as *mut F));
+}
+
+/// Returns the [SparseSection] at the current position of the file, without moving it.
+fn file_section(fd: RawFd) -> io::Result<SparseSection> {
+    let current = lseek(fd, 0, libc::SEEK_CUR)?;
+    let section = match lseek(fd, current as libc::off_t, libc::SEEK_DATA) {
+        Ok(data) if data > current => SparseSection::Hole(data - current),
+        Ok(_) => {
+            let hole = lseek(fd, current as libc::off_t, libc::SEEK_HOLE)?;
+            SparseSection::Data(hole - current)
+        }
+        // There is no more data after the current position, only a trailing hole.
+        Err(e) if e.raw_os_error() == Some(libc::ENXIO) => {
+            let end = lseek(fd, 0, libc::SEEK_END)?;
+            SparseSection::Hole(end.saturating_sub(current))
+        }
+        Err(e) => return Err(e),
+    };
+    lseek(fd, current as libc::off_t, libc::SEEK_SET)?;
+    Ok(section)
+}
+
+fn lseek(fd: RawFd, offset: libc::off_t, whence: c_int) -> io::Result<u64> {
+    match unsafe { libc::lseek(fd, offset, whence) } {
+        -1 => Err(io::Error::last_os_error()),
+        position => Ok(position as u64),
+    }
+}
+
+fn seek_offset(length: u64) -> io::Result<i64> {
+    i64::try_from(length).map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "Hole too large"))
+}
+
+impl crate::Wrapper for Stream {
+    type Ptr = virt_sys::virStreamPtr;
+
+    /// The stream might be attached to a transfer already, so it is treated as active.
+    unsafe fn from_ptr(ptr: Self::Ptr) -> Self {
+        Self {
+            ptr,
+            state: State::Active,
+        }
+    }
+
+    fn as_ptr(&self) -> Self::Ptr {
+        self.ptr
+    }
+}
+
+impl Drop for Stream {
+    fn drop(&mut self) {
+        match self.state {
+            State::Active => {
+                if let Err(e) = self.abort_internal() {
+                    log::error!("Error when aborting unfinished stream: {}", e);
+                }
+            }
+            // The caller might have attached the stream through its raw pointer, so it is
+            // aborted all the same. Aborting a stream that was never attached fails, which is
+            // expected and not worth logging.
+            State::Unattached => {
+                let _ = self.abort_internal();
+            }
+            State::Aborted => {}
+        }
+        if let Err(e) = self.free_internal() {
+            log::error!("Error when freeing stream: {}", e);
+        }
+    }
+}
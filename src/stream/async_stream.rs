@@ -0,0 +1,182 @@
+use super::{io_error, Stream, StreamEvents};
+use crate::{VirtError, Wrapper};
+use std::{
+    io,
+    os::raw::c_int,
+    pin::Pin,
+    sync::{Arc, Mutex, MutexGuard},
+    task::{Context, Poll, Waker},
+};
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+
+/// A [Stream] implementing tokio's [AsyncRead] and [AsyncWrite].
+///
+/// The stream must have been created with [StreamFlags::NONBLOCK](super::StreamFlags::NONBLOCK),
+/// and the libvirt event loop must be running, see [crate::event]. Tasks are woken from the
+/// event loop when the stream becomes readable or writable.
+///
+/// Shutting the stream down, via [AsyncWrite::poll_shutdown], finishes it with
+/// [Stream::finish]. This is a blocking call. A stream that is dropped without being shut down
+/// is aborted.
+pub struct AsyncStream {
+    stream: Option<Stream>,
+    state: Arc<Mutex<State>>,
+}
+
+/// The tasks waiting for the stream, and the events the stream is currently watched for.
+#[derive(Default)]
+struct State {
+    read_waker: Option<Waker>,
+    write_waker: Option<Waker>,
+    subscribed: StreamEvents,
+}
+
+impl State {
+    /// Makes sure the stream is only watched for the events someone is waiting for. Watching
+    /// for events nobody waits for would make the event loop spin, since stream events are
+    /// level triggered.
+    fn update_subscription(&mut self, stream: virt_sys::virStreamPtr) -> Result<(), VirtError> {
+        let mut events = StreamEvents::empty();
+        if self.read_waker.is_some() {
+            events |= StreamEvents::READABLE;
+        }
+        if self.write_waker.is_some() {
+            events |= StreamEvents::WRITABLE;
+        }
+        if !events.is_empty() {
+            events |= StreamEvents::ERROR | StreamEvents::HANGUP;
+        }
+        if events != self.subscribed {
+            match unsafe { virt_sys::virStreamEventUpdateCallback(stream, events.bits() as c_int) }
+            {
+                -1 => return Err(VirtError::last_virt_error()),
+                _ => self.subscribed = events,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl AsyncStream {
+    /// Wraps a non-blocking stream. Fails if an event callback can't be registered on it, for
+    /// example because it already has one.
+    pub fn new(mut stream: Stream) -> Result<Self, VirtError> {
+        let state = Arc::new(Mutex::new(State::default()));
+        let callback_state = state.clone();
+        stream.add_event_callback_raw(StreamEvents::empty(), move |stream, events| {
+            let mut state = lock(&callback_state);
+            let hangup = events.intersects(StreamEvents::ERROR | StreamEvents::HANGUP);
+            let read_waker = if hangup || events.contains(StreamEvents::READABLE) {
+                state.read_waker.take()
+            } else {
+                None
+            };
+            let write_waker = if hangup || events.contains(StreamEvents::WRITABLE) {
+                state.write_waker.take()
+            } else {
+                None
+            };
+            if let Err(e) = state.update_subscription(stream) {
+                log::error!("Unable to update stream event subscription: {}", e);
+            }
+            drop(state);
+            read_waker
+                .into_iter()
+                .chain(write_waker)
+                .for_each(Waker::wake);
+        })?;
+        Ok(AsyncStream {
+            stream: Some(stream),
+            state,
+        })
+    }
+
+    /// Unwraps the underlying [Stream]. Returns `None` if the stream has already been shut down.
+    pub fn into_inner(mut self) -> Option<Stream> {
+        let mut stream = self.stream.take()?;
+        if let Err(e) = stream.remove_event_callback() {
+            log::error!("Unable to remove stream event callback: {}", e);
+        }
+        Some(stream)
+    }
+
+    /// Performs a non-blocking operation on the stream, registering the task to be woken up
+    /// when `event` occurs if the operation would block.
+    fn poll_io<T>(
+        &mut self,
+        cx: &mut Context<'_>,
+        event: StreamEvents,
+        operation: impl FnOnce(&mut Stream) -> Result<Option<T>, VirtError>,
+    ) -> Poll<io::Result<T>> {
+        let stream = match &mut self.stream {
+            Some(stream) => stream,
+            None => return Poll::Ready(Err(io::ErrorKind::NotConnected.into())),
+        };
+        // Register interest before trying the operation, so an event arriving in between is
+        // not lost.
+        {
+            let mut state = lock(&self.state);
+            let waker = if event == StreamEvents::READABLE {
+                &mut state.read_waker
+            } else {
+                &mut state.write_waker
+            };
+            *waker = Some(cx.waker().clone());
+            if let Err(e) = state.update_subscription(stream.as_ptr()) {
+                return Poll::Ready(Err(io_error(e)));
+            }
+        }
+        match operation(stream) {
+            Ok(Some(value)) => Poll::Ready(Ok(value)),
+            Ok(None) => Poll::Pending,
+            Err(e) => Poll::Ready(Err(io_error(e))),
+        }
+    }
+}
+
+fn lock(state: &Mutex<State>) -> MutexGuard<'_, State> {
+    state.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+impl AsyncRead for AsyncStream {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        if buf.remaining() == 0 {
+            return Poll::Ready(Ok(()));
+        }
+        let unfilled = buf.initialize_unfilled();
+        let result = self
+            .get_mut()
+            .poll_io(cx, StreamEvents::READABLE, |stream| stream.recv(unfilled));
+        result.map_ok(|n| buf.advance(n))
+    }
+}
+
+impl AsyncWrite for AsyncStream {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        if buf.is_empty() {
+            return Poll::Ready(Ok(0));
+        }
+        self.get_mut()
+            .poll_io(cx, StreamEvents::WRITABLE, |stream| stream.send(buf))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let result = match self.get_mut().stream.take() {
+            Some(stream) => stream.finish().map_err(io_error),
+            None => Ok(()),
+        };
+        Poll::Ready(result)
+    }
+}
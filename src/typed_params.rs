@@ -0,0 +1,227 @@
+use crate::{util::copy_string, Error, VirtError};
+use std::{
+    ffi::CStr,
+    io, mem,
+    os::raw::{c_char, c_int},
+    ptr,
+};
+
+/// An owned list of typed parameters, used to pass a variable set of named values to libvirt.
+/// The list is allocated and grown by libvirt's `virTypedParamsAdd*` functions.
+pub(crate) struct TypedParams {
+    ptr: virt_sys::virTypedParameterPtr,
+    len: c_int,
+    capacity: c_int,
+}
+
+impl TypedParams {
+    pub fn new() -> Self {
+        TypedParams {
+            ptr: ptr::null_mut(),
+            len: 0,
+            capacity: 0,
+        }
+    }
+
+    /// Takes ownership of a list of typed parameters allocated by libvirt.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must point to `len` valid typed parameters, allocated by libvirt, or be null if
+    /// `len` is zero.
+    pub unsafe fn from_raw(ptr: virt_sys::virTypedParameterPtr, len: c_int) -> Self {
+        TypedParams {
+            ptr,
+            len,
+            capacity: len,
+        }
+    }
+
+    /// Fetches a list of typed parameters from a libvirt API that fills in a list allocated by
+    /// the caller. `get` is handed the list and its length. It is first called with a null
+    /// list, to learn how many parameters there are, then with a list of that length to fill
+    /// in.
+    pub fn fetch<F>(mut get: F) -> Result<Self, Error>
+    where
+        F: FnMut(virt_sys::virTypedParameterPtr, &mut c_int) -> c_int,
+    {
+        let mut len = 0;
+        if get(ptr::null_mut(), &mut len) == -1 {
+            return Err(Error::from(VirtError::last_virt_error()));
+        }
+        // The list is freed by virTypedParamsFree, so it must come from the C allocator.
+        let ptr = unsafe {
+            libc::calloc(
+                len.max(0) as usize,
+                mem::size_of::<virt_sys::virTypedParameter>(),
+            )
+        } as virt_sys::virTypedParameterPtr;
+        if ptr.is_null() && len > 0 {
+            return Err(Error::Io(io::ErrorKind::OutOfMemory.into()));
+        }
+        // Zeroed parameters don't have a valid type, but they own no memory either, so the list
+        // can be freed whether or not libvirt fills it in.
+        let mut params = TypedParams {
+            ptr,
+            len,
+            capacity: len,
+        };
+        match get(params.ptr, &mut params.len) {
+            -1 => Err(Error::from(VirtError::last_virt_error())),
+            _ => Ok(params),
+        }
+    }
+
+    pub fn as_ptr(&self) -> virt_sys::virTypedParameterPtr {
+        self.ptr
+    }
+
+    pub fn len(&self) -> c_int {
+        self.len
+    }
+
+    pub fn add_int(&mut self, name: &'static [u8], value: i32) -> Result<(), VirtError> {
+        let name = param_name(name);
+        cvt(unsafe {
+            virt_sys::virTypedParamsAddInt(
+                &mut self.ptr,
+                &mut self.len,
+                &mut self.capacity,
+                name,
+                value,
+            )
+        })
+    }
+
+    pub fn add_uint(&mut self, name: &'static [u8], value: u32) -> Result<(), VirtError> {
+        let name = param_name(name);
+        cvt(unsafe {
+            virt_sys::virTypedParamsAddUInt(
+                &mut self.ptr,
+                &mut self.len,
+                &mut self.capacity,
+                name,
+                value,
+            )
+        })
+    }
+
+    pub fn add_ullong(&mut self, name: &'static [u8], value: u64) -> Result<(), VirtError> {
+        let name = param_name(name);
+        cvt(unsafe {
+            virt_sys::virTypedParamsAddULLong(
+                &mut self.ptr,
+                &mut self.len,
+                &mut self.capacity,
+                name,
+                value,
+            )
+        })
+    }
+
+    pub fn add_boolean(&mut self, name: &'static [u8], value: bool) -> Result<(), VirtError> {
+        let name = param_name(name);
+        cvt(unsafe {
+            virt_sys::virTypedParamsAddBoolean(
+                &mut self.ptr,
+                &mut self.len,
+                &mut self.capacity,
+                name,
+                value as c_int,
+            )
+        })
+    }
+
+    /// Adds a string parameter. Adding the same name multiple times is allowed, and is how
+    /// libvirt represents lists of strings.
+    pub fn add_string(&mut self, name: &'static [u8], value: &CStr) -> Result<(), VirtError> {
+        let name = param_name(name);
+        cvt(unsafe {
+            virt_sys::virTypedParamsAddString(
+                &mut self.ptr,
+                &mut self.len,
+                &mut self.capacity,
+                name,
+                value.as_ptr(),
+            )
+        })
+    }
+
+    /// Returns the value of the `int` parameter with the given name, if present.
+    pub fn get_int(&self, name: &[u8]) -> Result<Option<i32>, VirtError> {
+        let mut value = 0;
+        let ret = unsafe {
+            virt_sys::virTypedParamsGetInt(self.ptr, self.len, param_name(name), &mut value)
+        };
+        cvt_get(ret, value)
+    }
+
+    /// Returns the value of the `unsigned int` parameter with the given name, if present.
+    pub fn get_uint(&self, name: &[u8]) -> Result<Option<u32>, VirtError> {
+        let mut value = 0;
+        let ret = unsafe {
+            virt_sys::virTypedParamsGetUInt(self.ptr, self.len, param_name(name), &mut value)
+        };
+        cvt_get(ret, value)
+    }
+
+    /// Returns the value of the `unsigned long long` parameter with the given name, if present.
+    pub fn get_ullong(&self, name: &[u8]) -> Result<Option<u64>, VirtError> {
+        let mut value = 0;
+        let ret = unsafe {
+            virt_sys::virTypedParamsGetULLong(self.ptr, self.len, param_name(name), &mut value)
+        };
+        cvt_get(ret, value)
+    }
+
+    /// Returns the value of the boolean parameter with the given name, if present.
+    pub fn get_boolean(&self, name: &[u8]) -> Result<Option<bool>, VirtError> {
+        let mut value = 0;
+        let ret = unsafe {
+            virt_sys::virTypedParamsGetBoolean(self.ptr, self.len, param_name(name), &mut value)
+        };
+        cvt_get(ret, value != 0)
+    }
+
+    /// Returns a copy of the value of the string parameter with the given name, if present.
+    pub fn get_string(&self, name: &[u8]) -> Result<Option<String>, Error> {
+        let mut value = ptr::null();
+        let ret = unsafe {
+            virt_sys::virTypedParamsGetString(self.ptr, self.len, param_name(name), &mut value)
+        };
+        match cvt_get(ret, value)? {
+            Some(value) => Ok(Some(unsafe { copy_string(value) }?)),
+            None => Ok(None),
+        }
+    }
+}
+
+impl Drop for TypedParams {
+    fn drop(&mut self) {
+        unsafe { virt_sys::virTypedParamsFree(self.ptr, self.len) }
+    }
+}
+
+/// Converts a nul terminated parameter name, such as the constants from `virt_sys`, into a
+/// pointer.
+fn param_name(name: &[u8]) -> *const c_char {
+    assert_eq!(name.last(), Some(&0), "Parameter name not nul terminated");
+    name.as_ptr() as *const c_char
+}
+
+fn cvt(ret: c_int) -> Result<(), VirtError> {
+    match ret {
+        -1 => Err(VirtError::last_virt_error()),
+        _ => Ok(()),
+    }
+}
+
+/// Converts the return value of a `virTypedParamsGet*` function. They return 1 if the parameter
+/// was found and 0 if it was not.
+fn cvt_get<T>(ret: c_int, value: T) -> Result<Option<T>, VirtError> {
+    match ret {
+        -1 => Err(VirtError::last_virt_error()),
+        0 => Ok(None),
+        _ => Ok(Some(value)),
+    }
+}
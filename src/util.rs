@@ -0,0 +1,86 @@
+use crate::{Error, Wrapper};
+use std::{
+    ffi::{CStr, CString},
+    os::{raw::c_char, unix::ffi::OsStrExt},
+    path::Path,
+    ptr, slice,
+};
+
+/// Takes ownership of a string allocated by libvirt, copies it into a Rust `String` and frees
+/// the original.
+///
+/// # Safety
+///
+/// `ptr` must point to a valid, nul terminated, string allocated with `malloc`. The pointer is
+/// invalid after this function returns.
+pub(crate) unsafe fn take_string(ptr: *mut c_char) -> Result<String, Error> {
+    let string = copy_string(ptr);
+    libc::free(ptr as *mut _);
+    string
+}
+
+/// Copies a string owned by libvirt into a Rust `String`, leaving the original untouched.
+///
+/// # Safety
+///
+/// `ptr` must point to a valid, nul terminated, string.
+pub(crate) unsafe fn copy_string(ptr: *const c_char) -> Result<String, Error> {
+    CStr::from_ptr(ptr)
+        .to_str()
+        .map(str::to_owned)
+        .map_err(Error::Utf8Error)
+}
+
+/// Like [copy_string], but returns `None` for a null pointer.
+///
+/// # Safety
+///
+/// `ptr` must be null, or point to a valid, nul terminated, string.
+pub(crate) unsafe fn copy_opt_string(ptr: *const c_char) -> Result<Option<String>, Error> {
+    if ptr.is_null() {
+        Ok(None)
+    } else {
+        copy_string(ptr).map(Some)
+    }
+}
+
+/// Converts a filesystem path into a C string that can be handed to libvirt.
+pub(crate) fn path_cstring(path: &Path) -> Result<CString, Error> {
+    CString::new(path.as_os_str().as_bytes()).map_err(Error::InvalidPath)
+}
+
+/// Converts an optional XML document into an optional C string.
+pub(crate) fn xml_cstring(xml: Option<&str>) -> Result<Option<CString>, Error> {
+    xml.map(CString::new).transpose().map_err(Error::InvalidXml)
+}
+
+/// Returns the pointer to the given C string, or a null pointer if there is no string.
+pub(crate) fn opt_ptr(cstr: &Option<CString>) -> *const c_char {
+    match cstr {
+        Some(cstr) => cstr.as_ptr(),
+        None => ptr::null(),
+    }
+}
+
+/// Takes ownership of an array of object pointers allocated by libvirt, such as the ones
+/// returned by the `virConnectListAll*` family of functions. Each pointer is wrapped in `T` and
+/// the array itself is freed.
+///
+/// # Safety
+///
+/// `array` must point to `len` valid pointers that the caller owns a reference to, in an array
+/// allocated with `malloc`. The array pointer is invalid after this function returns.
+pub(crate) unsafe fn take_wrapper_array<T: Wrapper>(array: *mut T::Ptr, len: usize) -> Vec<T>
+where
+    T::Ptr: Copy,
+{
+    if array.is_null() {
+        return Vec::new();
+    }
+    let wrappers = slice::from_raw_parts(array, len)
+        .iter()
+        .map(|&ptr| T::from_ptr(ptr))
+        .collect();
+    libc::free(array as *mut _);
+    wrappers
+}